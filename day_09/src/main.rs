@@ -1,79 +1,177 @@
-fn main() {
+use std::{error::Error, fmt::Display};
+
+fn main() -> Result<(), Box<dyn Error>> {
     let input = read_input();
     let readings = process_input(&input);
 
-    let part_one_answer = part_one_solution(&readings);
+    if std::env::args().any(|arg| arg == "--report") {
+        println!("{}", report(&readings));
+    }
+
+    let part_one_answer = part_one_solution(&readings)?;
     println!("Part One answer is {part_one_answer}");
+
+    let part_two_answer = part_two_solution(&readings)?;
+    println!("Part Two answer is {part_two_answer}");
+
+    Ok(())
 }
 
-fn part_one_solution(readings: &[Vec<i32>]) -> i32 {
-    readings.iter().fold(0, |acc, reading| {
-        let next_sequences = calculate_next_sequences(reading);
-        acc + next_sequences.last().unwrap().last().unwrap()
+fn part_one_solution(readings: &[Vec<i64>]) -> Result<i64, OasisError> {
+    readings.iter().try_fold(0_i64, |acc, reading| {
+        let analysis = analyse(reading)?;
+        acc.checked_add(analysis.next).ok_or(OasisError::Overflow)
     })
 }
 
-fn calculate_next_sequences(reading: &[i32]) -> Vec<Vec<i32>> {
-    let mut diffs = generate_diffs(reading);
-    diffs.reverse();
-
-    for num in 0..(diffs.len() - 1) {
-        let first = *diffs[num].last().unwrap();
-        let last = &mut diffs[num + 1];
+fn part_two_solution(readings: &[Vec<i64>]) -> Result<i64, OasisError> {
+    readings.iter().try_fold(0_i64, |acc, reading| {
+        let analysis = analyse(reading)?;
+        acc.checked_add(analysis.previous)
+            .ok_or(OasisError::Overflow)
+    })
+}
 
-        let next_sequence = first + last.last().unwrap();
-        last.push(next_sequence);
-    }
+fn report(readings: &[Vec<i64>]) -> String {
+    readings
+        .iter()
+        .enumerate()
+        .map(|(idx, reading)| match analyse(reading) {
+            Ok(analysis) => format!(
+                "Reading {}: degree {}, previous {}, next {}",
+                idx + 1,
+                analysis.degree,
+                analysis.previous,
+                analysis.next
+            ),
+            Err(err) => format!("Reading {}: {err}", idx + 1),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
 
-    diffs
+fn analyse(reading: &[i64]) -> Result<Analysis, OasisError> {
+    Ok(Analysis {
+        degree: polynomial_degree(reading)?,
+        previous: extrapolate(reading, -1)?,
+        next: extrapolate(reading, reading.len() as i64)?,
+    })
 }
 
-fn generate_diffs(reading: &[i32]) -> Vec<Vec<i32>> {
-    let mut diffs: Vec<Vec<i32>> = vec![reading.to_vec()];
+// The degree is how many rounds of differences it takes to reach a row of all zeros.
+// That row needs at least one value in it to count, otherwise the reading is too short
+// to tell what it's doing
+fn polynomial_degree(reading: &[i64]) -> Result<usize, OasisError> {
+    let mut row = reading.to_vec();
 
-    while !diffs.last().unwrap().iter().all(|num| num == &0) {
-        let diff = generate_differential_array(diffs.last().unwrap());
+    for degree in 0..reading.len().saturating_sub(1) {
+        for pos in 0..(row.len() - 1) {
+            row[pos] = row[pos + 1]
+                .checked_sub(row[pos])
+                .ok_or(OasisError::Overflow)?;
+        }
+        row.pop();
 
-        diffs.push(diff);
+        if row.iter().all(|num| num == &0) {
+            return Ok(degree);
+        }
     }
 
-    diffs
+    Err(OasisError::NotPolynomial {
+        length: reading.len(),
+    })
 }
 
-fn generate_differential_array(reading: &[i32]) -> Vec<i32> {
-    let mut diff: Vec<i32> = vec![];
+// k is the position in the reading's own indexing, so reading.len() is the next value
+// and -1 is the one before the first
+fn extrapolate(reading: &[i64], k: i64) -> Result<i64, OasisError> {
+    // a degree d polynomial is fixed by any d + 1 of its points, so the rest can be ignored
+    let points = &reading[..=polynomial_degree(reading)?];
+    let coefficients = lagrange_coefficients(points.len(), k).ok_or(OasisError::Overflow)?;
 
-    for pos in 0..(reading.len() - 1) {
-        let first = reading[pos];
-        let next = reading[pos + 1];
+    let value = points
+        .iter()
+        .zip(coefficients)
+        .try_fold(0_i128, |acc, (&value, coefficient)| {
+            acc.checked_add(coefficient.checked_mul(value as i128)?)
+        })
+        .ok_or(OasisError::Overflow)?;
 
-        // todo: how to handle if the diff is a negative number??
-        diff.push(next - first);
-    }
+    i64::try_from(value).map_err(|_| OasisError::Overflow)
+}
 
-    diff
+// The Lagrange basis for the points 0..n evaluated at an integer k simplifies to
+// (-1)^(n-1-i) * C(k, i) * C(k-i-1, n-1-i), so it can be worked out without
+// building any of the difference rows
+fn lagrange_coefficients(n: usize, k: i64) -> Option<Vec<i128>> {
+    (0..n)
+        .map(|i| {
+            let remaining = n - 1 - i;
+            let sign = (-1_i128).pow(remaining as u32);
+
+            let coefficient = binomial(k as i128, i)?
+                .checked_mul(binomial(k as i128 - i as i128 - 1, remaining)?)?;
+
+            coefficient.checked_mul(sign)
+        })
+        .collect()
+}
+
+// Generalised binomial coefficient, valid for negative n as well
+fn binomial(n: i128, k: usize) -> Option<i128> {
+    (0..k).try_fold(1_i128, |acc, j| {
+        let j = j as i128;
+        // acc * (n - j) is always divisible by (j + 1) as it's C(n, j + 1) * (j + 1)
+        Some(acc.checked_mul(n - j)? / (j + 1))
+    })
 }
 
 fn read_input() -> String {
     include_str!("../data/puzzle_input.txt").to_string()
 }
 
-fn process_input(input: &str) -> Vec<Vec<i32>> {
+fn process_input(input: &str) -> Vec<Vec<i64>> {
     input
         .lines()
         .map(|line| {
             line.split_whitespace()
-                .map(|reading| reading.parse::<i32>().unwrap())
+                .map(|reading| reading.parse::<i64>().unwrap())
                 .collect()
         })
         .collect()
 }
 
+#[derive(Debug, PartialEq)]
+struct Analysis {
+    degree: usize,
+    previous: i64,
+    next: i64,
+}
+
+#[derive(Debug, PartialEq)]
+enum OasisError {
+    NotPolynomial { length: usize },
+    Overflow,
+}
+
+impl Display for OasisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotPolynomial { length } => {
+                write!(f, "reading is not polynomial within its {length} values")
+            }
+            Self::Overflow => write!(f, "reading overflowed while extrapolating"),
+        }
+    }
+}
+
+impl Error for OasisError {}
+
 #[cfg(test)]
 mod test_super {
     use super::*;
 
-    fn test_data() -> Vec<Vec<i32>> {
+    fn test_data() -> Vec<Vec<i64>> {
         process_input(include_str!("../data/test_input.txt"))
     }
 
@@ -86,45 +184,92 @@ mod test_super {
     }
 
     #[test]
-    fn test_generate_differential_array() {
-        let diff_array = generate_differential_array(&[0, 3, 6, 9, 12, 15]);
+    fn test_binomial() {
+        assert_eq!(binomial(5, 2), Some(10));
+        assert_eq!(binomial(5, 0), Some(1));
+        assert_eq!(binomial(2, 5), Some(0));
+        assert_eq!(binomial(-1, 3), Some(-1));
+        assert_eq!(binomial(-3, 2), Some(6));
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        let reading = [10, 13, 16, 21, 30, 45];
+
+        assert_eq!(extrapolate(&reading, 6), Ok(68));
+        assert_eq!(extrapolate(&reading, 7), Ok(101));
+        assert_eq!(extrapolate(&reading, -1), Ok(5));
+        assert_eq!(extrapolate(&reading, -2), Ok(-4));
+        // positions inside the reading give back the original value
+        assert_eq!(extrapolate(&reading, 3), Ok(21));
+    }
+
+    #[test]
+    fn test_extrapolate_overflow() {
+        let reading = [0, i64::MAX / 2, i64::MAX - 1];
 
-        assert_eq!(diff_array, vec![3, 3, 3, 3, 3]);
+        assert_eq!(extrapolate(&reading, 3), Err(OasisError::Overflow));
+        assert_eq!(extrapolate(&reading, -1), Ok(-(i64::MAX / 2)));
     }
 
     #[test]
-    fn test_generate_diffs() {
-        let diffs = generate_diffs(&[0, 3, 6, 9, 12, 15]);
+    fn test_polynomial_degree() {
+        assert_eq!(polynomial_degree(&[0, 3, 6, 9, 12, 15]), Ok(1));
+        assert_eq!(polynomial_degree(&[1, 3, 6, 10, 15, 21]), Ok(2));
+        assert_eq!(polynomial_degree(&[10, 13, 16, 21, 30, 45]), Ok(3));
+        assert_eq!(polynomial_degree(&[7, 7]), Ok(0));
+    }
 
-        assert_eq!(diffs.len(), 3);
+    #[test]
+    fn test_polynomial_degree_not_polynomial() {
+        assert_eq!(
+            polynomial_degree(&[1, 2, 4, 8, 16]),
+            Err(OasisError::NotPolynomial { length: 5 })
+        );
+        assert_eq!(
+            polynomial_degree(&[5]),
+            Err(OasisError::NotPolynomial { length: 1 })
+        );
         assert_eq!(
-            diffs,
-            vec![
-                vec![0, 3, 6, 9, 12, 15,],
-                vec![3, 3, 3, 3, 3],
-                vec![0, 0, 0, 0,]
-            ]
+            polynomial_degree(&[]),
+            Err(OasisError::NotPolynomial { length: 0 })
         );
     }
 
     #[test]
-    fn test_calculate_next_sequences() {
-        let next_sequences = calculate_next_sequences(&[0, 3, 6, 9, 12, 15]);
+    fn test_analyse() {
+        assert_eq!(
+            analyse(&[10, 13, 16, 21, 30, 45]),
+            Ok(Analysis {
+                degree: 3,
+                previous: 5,
+                next: 68
+            })
+        );
+        assert_eq!(
+            analyse(&[1, 2, 4, 8, 16]),
+            Err(OasisError::NotPolynomial { length: 5 })
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let mut readings = test_data();
+        readings.push(vec![1, 2, 4, 8]);
 
         assert_eq!(
-            next_sequences,
-            vec![
-                vec![0, 0, 0, 0,],
-                vec![3, 3, 3, 3, 3, 3],
-                vec![0, 3, 6, 9, 12, 15, 18],
-            ]
-        )
+            report(&readings),
+            "Reading 1: degree 1, previous -3, next 18\n\
+             Reading 2: degree 2, previous 0, next 28\n\
+             Reading 3: degree 3, previous 5, next 68\n\
+             Reading 4: reading is not polynomial within its 4 values"
+        );
     }
 
     #[test]
     fn test_part_one_example() {
         let readings = test_data();
-        let part_one_answer = part_one_solution(&readings);
+        let part_one_answer = part_one_solution(&readings).unwrap();
 
         assert_eq!(part_one_answer, 114);
     }
@@ -132,8 +277,24 @@ mod test_super {
     #[test]
     fn test_part_one_actual() {
         let readings = process_input(include_str!("../data/puzzle_input.txt"));
-        let part_one_answer = part_one_solution(&readings);
+        let part_one_answer = part_one_solution(&readings).unwrap();
 
         assert_eq!(part_one_answer, 2008960228);
     }
+
+    #[test]
+    fn test_part_two_example() {
+        let readings = test_data();
+        let part_two_answer = part_two_solution(&readings).unwrap();
+
+        assert_eq!(part_two_answer, 2);
+    }
+
+    #[test]
+    fn test_part_two_actual() {
+        let readings = process_input(include_str!("../data/puzzle_input.txt"));
+        let part_two_answer = part_two_solution(&readings).unwrap();
+
+        assert_eq!(part_two_answer, 1097);
+    }
 }
@@ -1,55 +1,150 @@
 // TODO: Remove ndarray. I'm pretty sure I can do this with just a Vec<Vec<T>>
 // just need to remember that x,y and are reversed when accessing it
 
-use std::str::FromStr;
+mod render;
+
+use std::{collections::HashSet, error::Error, str::FromStr};
 
 use ndarray::Array2;
+use render::{render, RenderMode};
 use strum::EnumString;
 
 type TileSet = Array2<Tile>;
 
-fn main() {
+fn main() -> Result<(), Box<dyn Error>> {
     let input = include_str!("../data/puzzle_input.txt");
     let tiles = process_input(input);
+    let pipe_loop = extract_loop(&tiles)?;
 
-    let part_one_answer = part_one_solution(&tiles);
+    let part_one_answer = part_one_solution(&pipe_loop);
     println!("The Part One solution is: {part_one_answer}");
+
+    let part_two_answer = part_two_solution(&tiles, &pipe_loop);
+    println!("The Part Two solution is: {part_two_answer}");
+
+    let render_mode = std::env::args().find_map(|arg| match arg.as_str() {
+        "--render" => Some(RenderMode::Ansi),
+        "--render=plain" => Some(RenderMode::Plain),
+        _ => None,
+    });
+
+    if let Some(mode) = render_mode {
+        let enclosed_tiles = find_enclosed_tiles(&tiles, &pipe_loop);
+        println!("{}", render(&tiles, &pipe_loop, &enclosed_tiles, mode));
+    }
+
+    Ok(())
 }
 
-fn part_one_solution(tiles: &TileSet) -> usize {
-    let start_pos = find_start_tile(tiles);
-    let start_surrounding_coords = find_surrounding_coordinates(start_pos);
-    let connected_tiles = find_connected_tiles(tiles, start_pos, &start_surrounding_coords);
+fn part_one_solution(pipe_loop: &Loop) -> usize {
+    // furtherst place from the start should be halfway around the loop
+    pipe_loop.coordinates.len() / 2
+}
 
-    if connected_tiles.len() != 2 {
-        panic!(
-            "Expected exactly 2 matching endpoints for the starting point, got: {}",
-            connected_tiles.len()
-        );
+fn part_two_solution(tiles: &TileSet, pipe_loop: &Loop) -> usize {
+    find_enclosed_tiles(tiles, pipe_loop).len()
+}
+
+fn find_enclosed_tiles(tiles: &TileSet, pipe_loop: &Loop) -> HashSet<(usize, usize)> {
+    let loop_coordinates: HashSet<&(usize, usize)> = pipe_loop.coordinates.iter().collect();
+
+    let mut enclosed_tiles = HashSet::new();
+
+    for row in tiles.rows() {
+        // scanning left to right, every loop pipe with a northern connection is a
+        // crossing of the loop boundary, so an odd count means we're inside it
+        let mut inside = false;
+
+        for tile in row {
+            if loop_coordinates.contains(&tile.coordinates) {
+                if [
+                    TileType::VerticalPipe,
+                    TileType::NorthEastPipe,
+                    TileType::NorthWestPipe,
+                ]
+                .contains(pipe_loop.tile_type(tile))
+                {
+                    inside = !inside;
+                }
+            } else if inside {
+                enclosed_tiles.insert(tile.coordinates);
+            }
+        }
     }
 
-    let mut path: Vec<&Tile> = vec![connected_tiles[0]];
-    let mut last_tile: &Tile = start_pos;
-    let mut current_tile = connected_tiles[0];
+    enclosed_tiles
+}
 
-    while current_tile.r#type != TileType::Start {
+fn extract_loop(tiles: &TileSet) -> Result<Loop, String> {
+    let start_pos = find_start_tile(tiles);
+    let start_type = start_tile_type(tiles, start_pos)?;
+
+    let mut coordinates = vec![start_pos.coordinates];
+    let mut last_coordinates = start_pos.coordinates;
+    let mut current_coordinates = start_type.connected_coordinates(start_pos.coordinates)[0];
+
+    while current_coordinates != start_pos.coordinates {
+        let current_tile = tiles
+            .get((current_coordinates.1, current_coordinates.0))
+            .ok_or(format!(
+                "The loop leaves the map at {current_coordinates:?}"
+            ))?;
         let connections = current_tile.connected_coordinates();
 
+        if !connections.contains(&last_coordinates) {
+            return Err(format!(
+                "The loop is broken at {current_coordinates:?}, {} does not connect back to {last_coordinates:?}",
+                current_tile.r#type
+            ));
+        }
+
         // remove the node we came from from the list
+        // a pipe on the edge pointing off the map only has the one connection
         let next_step = connections
             .into_iter()
-            .find(|conn| conn != &last_tile.coordinates)
-            .unwrap();
+            .find(|conn| conn != &last_coordinates)
+            .ok_or(format!(
+                "The loop leaves the map at {current_coordinates:?}"
+            ))?;
+
+        coordinates.push(current_coordinates);
+        last_coordinates = current_coordinates;
+        current_coordinates = next_step;
+    }
 
-        let next_tile = &tiles.get((next_step.1, next_step.0)).unwrap();
+    Ok(Loop {
+        start_type,
+        coordinates,
+    })
+}
 
-        path.push(next_tile);
-        last_tile = current_tile;
-        current_tile = next_tile;
+// S hides a real pipe, which has to be the one joining the two tiles connected to it
+fn start_tile_type(tiles: &TileSet, start: &Tile) -> Result<TileType, String> {
+    let surrounding_coords = find_surrounding_coordinates(start);
+    let connected_tiles = find_connected_tiles(tiles, start, &surrounding_coords);
+
+    if connected_tiles.len() != 2 {
+        return Err(format!(
+            "Expected exactly 2 matching endpoints for the starting point, got: {}",
+            connected_tiles.len()
+        ));
     }
 
-    // furtherst place from the start should be halfway through the path
-    path.len() / 2
+    let (x, y) = start.coordinates;
+    let north = connected_tiles.iter().any(|tile| tile.coordinates.1 < y);
+    let east = connected_tiles.iter().any(|tile| tile.coordinates.0 > x);
+    let south = connected_tiles.iter().any(|tile| tile.coordinates.1 > y);
+    let west = connected_tiles.iter().any(|tile| tile.coordinates.0 < x);
+
+    match (north, east, south, west) {
+        (true, false, true, false) => Ok(TileType::VerticalPipe),
+        (false, true, false, true) => Ok(TileType::HorizontalPipe),
+        (true, true, false, false) => Ok(TileType::NorthEastPipe),
+        (true, false, false, true) => Ok(TileType::NorthWestPipe),
+        (false, false, true, true) => Ok(TileType::SouthWestPipe),
+        (false, true, true, false) => Ok(TileType::SouthEastPipe),
+        _ => Err("Could not work out the pipe under the starting point".to_string()),
+    }
 }
 
 fn find_start_tile(tiles: &TileSet) -> &Tile {
@@ -139,6 +234,41 @@ enum TileType {
     Start,
 }
 
+impl TileType {
+    fn box_drawing(&self) -> char {
+        match self {
+            TileType::Ground => '.',
+            TileType::VerticalPipe => '│',
+            TileType::HorizontalPipe => '─',
+            TileType::NorthEastPipe => '└',
+            TileType::NorthWestPipe => '┘',
+            TileType::SouthWestPipe => '┐',
+            TileType::SouthEastPipe => '┌',
+            TileType::Start => 'S',
+        }
+    }
+
+    fn connected_coordinates(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        let north = y.checked_sub(1).map(|y| (x, y));
+        let east = Some((x + 1, y));
+        let south = Some((x, y + 1));
+        let west = x.checked_sub(1).map(|x| (x, y));
+
+        let connections = match self {
+            TileType::VerticalPipe => vec![south, north],
+            TileType::HorizontalPipe => vec![east, west],
+            TileType::NorthEastPipe => vec![north, east],
+            TileType::NorthWestPipe => vec![north, west],
+            TileType::SouthWestPipe => vec![south, west],
+            TileType::SouthEastPipe => vec![south, east],
+            // the start's real pipe has to be worked out from the tiles around it
+            TileType::Ground | TileType::Start => vec![],
+        };
+
+        connections.into_iter().flatten().collect()
+    }
+}
+
 #[derive(Clone, Debug)]
 struct Tile {
     r#type: TileType,
@@ -156,36 +286,7 @@ impl Tile {
     }
 
     fn connected_coordinates(&self) -> Vec<(usize, usize)> {
-        // I should technically do checked math to prevent an overflow
-        // but AoC inputs should be trusted to be correct and not lead
-        // me past the bounds (assuming my solution works properly)
-        match self.r#type {
-            TileType::VerticalPipe => vec![
-                (self.coordinates.0, self.coordinates.1 + 1),
-                (self.coordinates.0, self.coordinates.1 - 1),
-            ],
-            TileType::HorizontalPipe => vec![
-                (self.coordinates.0 + 1, self.coordinates.1),
-                (self.coordinates.0 - 1, self.coordinates.1),
-            ],
-            TileType::NorthEastPipe => vec![
-                (self.coordinates.0, self.coordinates.1 - 1),
-                (self.coordinates.0 + 1, self.coordinates.1),
-            ],
-            TileType::NorthWestPipe => vec![
-                (self.coordinates.0, self.coordinates.1 - 1),
-                (self.coordinates.0 - 1, self.coordinates.1),
-            ],
-            TileType::SouthWestPipe => vec![
-                (self.coordinates.0, self.coordinates.1 + 1),
-                (self.coordinates.0 - 1, self.coordinates.1),
-            ],
-            TileType::SouthEastPipe => vec![
-                (self.coordinates.0, self.coordinates.1 + 1),
-                (self.coordinates.0 + 1, self.coordinates.1),
-            ],
-            _ => panic!("Did not expect to run coordinates on a type without them"),
-        }
+        self.r#type.connected_coordinates(self.coordinates)
     }
 
     fn connected_to(&self, other: &Tile) -> bool {
@@ -223,6 +324,22 @@ impl Tile {
     }
 }
 
+#[derive(Debug)]
+struct Loop {
+    start_type: TileType,
+    coordinates: Vec<(usize, usize)>,
+}
+
+impl Loop {
+    fn tile_type<'a>(&'a self, tile: &'a Tile) -> &'a TileType {
+        if tile.r#type == TileType::Start {
+            &self.start_type
+        } else {
+            &tile.r#type
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_super {
     use super::*;
@@ -302,22 +419,137 @@ mod test_super {
         assert!(another_start.connected_to(&another_connected));
     }
 
+    #[test]
+    fn test_start_tile_type() {
+        let simple_data = simple_test_data();
+        let start_tile = find_start_tile(&simple_data);
+
+        assert_eq!(
+            start_tile_type(&simple_data, start_tile),
+            Ok(TileType::SouthEastPipe)
+        );
+
+        let complex_data = complex_test_data();
+        let start_tile = find_start_tile(&complex_data);
+
+        assert_eq!(
+            start_tile_type(&complex_data, start_tile),
+            Ok(TileType::SouthEastPipe)
+        );
+    }
+
+    #[test]
+    fn test_extract_loop() {
+        let simple_data = simple_test_data();
+        let pipe_loop = extract_loop(&simple_data).unwrap();
+
+        assert_eq!(pipe_loop.start_type, TileType::SouthEastPipe);
+        assert_eq!(
+            pipe_loop.coordinates,
+            vec![
+                (1, 1),
+                (1, 2),
+                (1, 3),
+                (2, 3),
+                (3, 3),
+                (3, 2),
+                (3, 1),
+                (2, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_loop_bad_start() {
+        // a pipe leading away from the loop isn't connected to the start
+        let stray_pipe = process_input(".....\n.S-7.\n.|.|.\n.L-J.\n..|..");
+        assert!(extract_loop(&stray_pipe).is_ok());
+
+        let three_connections = process_input(".|...\n.S-7.\n.|.|.\n.L-J.\n.....");
+        assert_eq!(
+            extract_loop(&three_connections).unwrap_err(),
+            "Expected exactly 2 matching endpoints for the starting point, got: 3"
+        );
+
+        let one_connection = process_input(".....\n.S-7.\n...|.\n.L-J.\n.....");
+        assert_eq!(
+            extract_loop(&one_connection).unwrap_err(),
+            "Expected exactly 2 matching endpoints for the starting point, got: 1"
+        );
+
+        let crossroads = process_input("..|..\n..|..\n--S--\n..|..\n..|..");
+        assert_eq!(
+            extract_loop(&crossroads).unwrap_err(),
+            "Expected exactly 2 matching endpoints for the starting point, got: 4"
+        );
+    }
+
+    #[test]
+    fn test_extract_loop_off_the_edge() {
+        let off_the_edge = process_input("-7\n.S\n-J");
+
+        assert_eq!(
+            extract_loop(&off_the_edge).unwrap_err(),
+            "The loop leaves the map at (0, 2)"
+        );
+    }
+
+    #[test]
+    fn test_extract_loop_broken() {
+        let broken = process_input(".....\n.S-7.\n.|.|.\n.L-..\n.....");
+
+        assert_eq!(
+            extract_loop(&broken).unwrap_err(),
+            "The loop is broken at (3, 3), . does not connect back to (2, 3)"
+        );
+    }
+
     #[test]
     fn test_part_one_test_solution() {
         let simple_data = simple_test_data();
 
-        assert_eq!(part_one_solution(&simple_data), 4);
+        let pipe_loop = extract_loop(&simple_data).unwrap();
+        assert_eq!(part_one_solution(&pipe_loop), 4);
 
         let complex_data = complex_test_data();
 
-        assert_eq!(part_one_solution(&complex_data), 8);
+        let pipe_loop = extract_loop(&complex_data).unwrap();
+        assert_eq!(part_one_solution(&pipe_loop), 8);
     }
 
     #[test]
     fn test_part_one_actual_solution() {
         let data = process_input(include_str!("../data/puzzle_input.txt"));
-        let part_one_answer = part_one_solution(&data);
+        let pipe_loop = extract_loop(&data).unwrap();
+        let part_one_answer = part_one_solution(&pipe_loop);
 
         assert_eq!(part_one_answer, 6870);
     }
+
+    #[test]
+    fn test_part_two_test_solution() {
+        let inputs = [
+            (include_str!("../data/part_two_test_input.txt"), 4),
+            (include_str!("../data/part_two_larger_test_input.txt"), 8),
+            (include_str!("../data/part_two_junk_test_input.txt"), 10),
+            (include_str!("../data/simple_test_input.txt"), 1),
+            (include_str!("../data/complex_test_input.txt"), 1),
+        ];
+
+        for (input, expected) in inputs {
+            let data = process_input(input);
+            let pipe_loop = extract_loop(&data).unwrap();
+
+            assert_eq!(part_two_solution(&data, &pipe_loop), expected);
+        }
+    }
+
+    #[test]
+    fn test_part_two_actual_solution() {
+        let data = process_input(include_str!("../data/puzzle_input.txt"));
+        let pipe_loop = extract_loop(&data).unwrap();
+        let part_two_answer = part_two_solution(&data, &pipe_loop);
+
+        assert_eq!(part_two_answer, 287);
+    }
 }
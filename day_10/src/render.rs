@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+
+use crate::{Loop, TileSet};
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    Ansi,
+    Plain,
+}
+
+pub fn render(
+    tiles: &TileSet,
+    pipe_loop: &Loop,
+    enclosed_tiles: &HashSet<(usize, usize)>,
+    mode: RenderMode,
+) -> String {
+    let loop_coordinates: HashSet<&(usize, usize)> = pipe_loop.coordinates.iter().collect();
+
+    tiles
+        .rows()
+        .into_iter()
+        .map(|row| {
+            row.iter()
+                .map(|tile| {
+                    // the start is drawn as whichever pipe is hiding under it
+                    let (glyph, style) = if enclosed_tiles.contains(&tile.coordinates) {
+                        ('I', GREEN)
+                    } else if loop_coordinates.contains(&tile.coordinates) {
+                        (pipe_loop.tile_type(tile).box_drawing(), BOLD)
+                    } else {
+                        (tile.r#type.box_drawing(), DIM)
+                    };
+
+                    match mode {
+                        RenderMode::Ansi => format!("{style}{glyph}{RESET}"),
+                        RenderMode::Plain => glyph.to_string(),
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test_super {
+    use super::*;
+    use crate::{extract_loop, find_enclosed_tiles, process_input};
+
+    fn render_input(input: &str, mode: RenderMode) -> String {
+        let tiles = process_input(input);
+        let pipe_loop = extract_loop(&tiles).unwrap();
+        let enclosed_tiles = find_enclosed_tiles(&tiles, &pipe_loop);
+
+        render(&tiles, &pipe_loop, &enclosed_tiles, mode)
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let rendered = render_input(
+            include_str!("../data/complex_test_input.txt"),
+            RenderMode::Plain,
+        );
+
+        assert_eq!(
+            rendered,
+            "..┌┐.\n\
+             .┌┘│.\n\
+             ┌┘I└┐\n\
+             │┌──┘\n\
+             └┘..."
+        );
+    }
+
+    #[test]
+    fn test_render_plain_junk() {
+        let rendered = render_input("-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF", RenderMode::Plain);
+
+        assert_eq!(
+            rendered,
+            "─└│┌┐\n\
+             ┐┌─┐│\n\
+             └│I││\n\
+             ─└─┘│\n\
+             └│─┘┌"
+        );
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let rendered = render_input(".....\n.S-7.\n.|.|.\n.L-J.\n.....", RenderMode::Ansi);
+        let rows: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(
+            rows[1],
+            "\x1b[2m.\x1b[0m\x1b[1m┌\x1b[0m\x1b[1m─\x1b[0m\x1b[1m┐\x1b[0m\x1b[2m.\x1b[0m"
+        );
+        assert_eq!(
+            rows[2],
+            "\x1b[2m.\x1b[0m\x1b[1m│\x1b[0m\x1b[32mI\x1b[0m\x1b[1m│\x1b[0m\x1b[2m.\x1b[0m"
+        );
+    }
+}
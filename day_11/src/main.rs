@@ -1,72 +1,164 @@
-// TODO: Redo _allllllllll_ the logic for building the map, it's super
-//      inefficient as is
+// listing every pair is quadratic, so only do it when there's few enough to read
+const DEBUG_PAIR_LIMIT: usize = 50;
+
 fn main() {
     let input = include_str!("../data/puzzle_input.txt");
     let galaxy_map = process_input(input);
-    let expanded_galaxy = expand(galaxy_map);
 
-    let part_one_answer = part_one_solution(&expanded_galaxy);
+    if std::env::args().any(|arg| arg == "--debug") {
+        match debug_pair_distances(&expand(&galaxy_map, 2)) {
+            Some(pairs) => println!("{pairs}"),
+            None => {
+                println!("Too many galaxies to list every pair, the limit is {DEBUG_PAIR_LIMIT}")
+            }
+        }
+    }
+
+    let part_one_answer = part_one_solution(&galaxy_map);
     println!("Part One Answer is {part_one_answer}");
+
+    let part_two_answer = part_two_solution(&galaxy_map);
+    println!("Part Two Answer is {part_two_answer}");
 }
-fn part_one_solution(galaxy_map: &[Vec<char>]) -> usize {
-    let galaxy_pairs = generate_pairs(galaxy_map);
 
-    galaxy_pairs
-        .iter()
-        .fold(0, |acc, pair| acc + calculate_distance(&pair.0, &pair.1))
+fn part_one_solution(galaxy_map: &GalaxyMap) -> u64 {
+    solve(galaxy_map, 2)
 }
 
-fn process_input(input: &str) -> Vec<Vec<char>> {
-    input.lines().map(|line| line.chars().collect()).collect()
+fn part_two_solution(galaxy_map: &GalaxyMap) -> u64 {
+    solve(galaxy_map, 1_000_000)
 }
 
-fn expand(galaxy_map: Vec<Vec<char>>) -> Vec<Vec<char>> {
-    let mut row_expanded: Vec<Vec<char>> = vec![];
+fn solve(galaxy_map: &GalaxyMap, expansion_factor: u64) -> u64 {
+    let galaxies = expand(galaxy_map, expansion_factor);
 
-    for row in &galaxy_map {
-        row_expanded.push(row.to_vec());
+    sum_pairwise_distances(&galaxies)
+}
 
-        if row.iter().all(|pos| pos == &'.') {
-            row_expanded.push(row.to_vec());
-        }
+// Manhattan distance splits into independent x and y parts, so each axis can be
+// summed on its own without ever building the pairs
+fn sum_pairwise_distances(galaxies: &[Coordinate]) -> u64 {
+    let xs = galaxies.iter().map(|galaxy| galaxy.0).collect();
+    let ys = galaxies.iter().map(|galaxy| galaxy.1).collect();
+
+    sum_axis_distances(xs) + sum_axis_distances(ys)
+}
+
+fn sum_axis_distances(mut positions: Vec<u64>) -> u64 {
+    positions.sort_unstable();
+
+    // once sorted, the position at idx is at least as far along as every one before it,
+    // so its distance to all of them is idx * position - (sum of the earlier positions)
+    let (total, _) =
+        positions
+            .iter()
+            .enumerate()
+            .fold((0, 0), |(total, prefix_sum), (idx, position)| {
+                (
+                    total + idx as u64 * position - prefix_sum,
+                    prefix_sum + position,
+                )
+            });
+
+    total
+}
+
+fn debug_pair_distances(galaxies: &[Coordinate]) -> Option<String> {
+    if galaxies.len() > DEBUG_PAIR_LIMIT {
+        return None;
     }
 
-    let mut final_expanded: Vec<Vec<char>> = vec![];
+    let lines: Vec<String> = generate_pairs(galaxies)
+        .iter()
+        .map(|(first, other)| {
+            format!(
+                "{first:?} -> {other:?}: {}",
+                calculate_distance(first, other)
+            )
+        })
+        .collect();
+
+    Some(lines.join("\n"))
+}
 
-    for row in &row_expanded {
-        let mut new_row: Vec<char> = vec![];
+type Coordinate = (u64, u64);
 
-        for (col_idx, col) in row.iter().enumerate() {
-            let col_data: Vec<char> = row_expanded.iter().map(|row| row[col_idx]).collect();
+struct GalaxyMap {
+    galaxies: Vec<(usize, usize)>,
+    // how many empty columns/rows come before each index, so expanding a
+    // galaxy is just an offset rather than rebuilding the whole map
+    empty_columns_before: Vec<u64>,
+    empty_rows_before: Vec<u64>,
+}
 
-            new_row.push(*col);
+fn process_input(input: &str) -> GalaxyMap {
+    let mut galaxies: Vec<(usize, usize)> = vec![];
+    let mut width = 0;
+    let mut height = 0;
 
-            if col_data.iter().all(|char| char == &'.') {
-                new_row.push('.');
+    for (y_index, row) in input.lines().enumerate() {
+        for (x_index, char) in row.chars().enumerate() {
+            if char == '#' {
+                galaxies.push((x_index, y_index));
             }
+            width = width.max(x_index + 1);
         }
-
-        final_expanded.push(new_row);
+        height = y_index + 1;
     }
 
-    final_expanded
+    let empty_columns_before = count_empty_before(galaxies.iter().map(|galaxy| galaxy.0), width);
+    let empty_rows_before = count_empty_before(galaxies.iter().map(|galaxy| galaxy.1), height);
+
+    GalaxyMap {
+        galaxies,
+        empty_columns_before,
+        empty_rows_before,
+    }
 }
 
-type Coordinate = (usize, usize);
+fn count_empty_before(occupied: impl Iterator<Item = usize>, size: usize) -> Vec<u64> {
+    let mut is_occupied = vec![false; size];
+    for idx in occupied {
+        is_occupied[idx] = true;
+    }
 
-fn generate_pairs(galaxy_map: &[Vec<char>]) -> Vec<(Coordinate, Coordinate)> {
-    let mut galaxy_pairs: Vec<(Coordinate, Coordinate)> = vec![];
+    let mut empty_before = Vec::with_capacity(size);
+    let mut empty_count = 0;
 
-    let mut galaxies: Vec<Coordinate> = vec![];
+    for occupied in is_occupied {
+        empty_before.push(empty_count);
 
-    for (y_index, row) in galaxy_map.iter().enumerate() {
-        for (x_index, char) in row.iter().enumerate() {
-            if char == &'#' {
-                galaxies.push((x_index, y_index));
-            }
+        if !occupied {
+            empty_count += 1;
         }
     }
 
+    empty_before
+}
+
+fn expand(galaxy_map: &GalaxyMap, expansion_factor: u64) -> Vec<Coordinate> {
+    // swap each empty row/column before a galaxy for expansion_factor copies of it.
+    // There can't be more empty ones before a galaxy than its own coordinate, so
+    // taking them away first can't underflow, even for a factor of 0
+    let grow = |coordinate: usize, empty_before: u64| {
+        coordinate as u64 - empty_before + empty_before * expansion_factor
+    };
+
+    galaxy_map
+        .galaxies
+        .iter()
+        .map(|&(x, y)| {
+            (
+                grow(x, galaxy_map.empty_columns_before[x]),
+                grow(y, galaxy_map.empty_rows_before[y]),
+            )
+        })
+        .collect()
+}
+
+fn generate_pairs(galaxies: &[Coordinate]) -> Vec<(Coordinate, Coordinate)> {
+    let mut galaxy_pairs: Vec<(Coordinate, Coordinate)> = vec![];
+
     for (idx, galaxy) in galaxies.iter().enumerate() {
         for other in galaxies.iter().skip(idx + 1) {
             galaxy_pairs.push((*galaxy, *other));
@@ -76,7 +168,7 @@ fn generate_pairs(galaxy_map: &[Vec<char>]) -> Vec<(Coordinate, Coordinate)> {
     galaxy_pairs
 }
 
-fn calculate_distance(first: &Coordinate, other: &Coordinate) -> usize {
+fn calculate_distance(first: &Coordinate, other: &Coordinate) -> u64 {
     let x_diff = first.0.abs_diff(other.0);
     let y_diff = first.1.abs_diff(other.1);
 
@@ -87,7 +179,7 @@ fn calculate_distance(first: &Coordinate, other: &Coordinate) -> usize {
 mod test_super {
     use super::*;
 
-    fn test_data() -> Vec<Vec<char>> {
+    fn test_data() -> GalaxyMap {
         let test_input = include_str!("../data/test_input.txt");
 
         process_input(test_input)
@@ -97,38 +189,40 @@ mod test_super {
     fn test_process_input() {
         let galaxy_map = test_data();
 
-        assert_eq!(galaxy_map.len(), 10);
+        assert_eq!(galaxy_map.galaxies.len(), 9);
+        assert_eq!(galaxy_map.galaxies[0], (3, 0));
+        assert_eq!(galaxy_map.galaxies[8], (4, 9));
 
-        let expected_first_line = vec!['.', '.', '.', '#', '.', '.', '.', '.', '.', '.'];
-        assert_eq!(galaxy_map[0], expected_first_line);
+        assert_eq!(
+            galaxy_map.empty_columns_before,
+            vec![0, 0, 0, 1, 1, 1, 2, 2, 2, 3]
+        );
+        assert_eq!(
+            galaxy_map.empty_rows_before,
+            vec![0, 0, 0, 0, 1, 1, 1, 1, 2, 2]
+        );
     }
 
     #[test]
     fn test_expand() {
         let galaxy_map = test_data();
-        let expanded_map = expand(galaxy_map);
+        let expanded = expand(&galaxy_map, 2);
 
-        // rows
-        assert_eq!(expanded_map.len(), 12);
-        assert_eq!(
-            expanded_map[0],
-            vec!['.', '.', '.', '.', '#', '.', '.', '.', '.', '.', '.', '.', '.']
-        );
+        assert_eq!(expanded[0], (4, 0));
+        assert_eq!(expanded[1], (9, 1));
+        assert_eq!(expanded[8], (5, 11));
 
-        assert_eq!(
-            expanded_map[1],
-            vec!['.', '.', '.', '.', '.', '.', '.', '.', '.', '#', '.', '.', '.',]
-        );
+        let expanded = expand(&galaxy_map, 10);
 
-        // cols
-        assert_eq!(expanded_map[0].len(), 13);
+        assert_eq!(expanded[0], (12, 0));
+        assert_eq!(expanded[8], (13, 27));
     }
 
     #[test]
     fn test_generate_pairs() {
         let galaxy_map = test_data();
-        let expanded_map = expand(galaxy_map);
-        let pairs = generate_pairs(&expanded_map);
+        let expanded = expand(&galaxy_map, 2);
+        let pairs = generate_pairs(&expanded);
 
         assert_eq!(pairs.len(), 36);
 
@@ -136,6 +230,43 @@ mod test_super {
         assert_eq!(pairs[35], ((0, 11), (5, 11)));
     }
 
+    #[test]
+    fn test_sum_axis_distances() {
+        assert_eq!(sum_axis_distances(vec![]), 0);
+        assert_eq!(sum_axis_distances(vec![5]), 0);
+        assert_eq!(sum_axis_distances(vec![7, 1, 4]), 12);
+        assert_eq!(sum_axis_distances(vec![3, 3, 3]), 0);
+    }
+
+    #[test]
+    fn test_sum_pairwise_distances() {
+        let galaxy_map = test_data();
+        let expanded = expand(&galaxy_map, 2);
+
+        let brute_force = generate_pairs(&expanded)
+            .iter()
+            .fold(0, |acc, pair| acc + calculate_distance(&pair.0, &pair.1));
+
+        assert_eq!(sum_pairwise_distances(&expanded), brute_force);
+        assert_eq!(sum_pairwise_distances(&expanded), 374);
+    }
+
+    #[test]
+    fn test_debug_pair_distances() {
+        let galaxy_map = test_data();
+        let expanded = expand(&galaxy_map, 2);
+        let debug_output = debug_pair_distances(&expanded).unwrap();
+        let lines: Vec<&str> = debug_output.lines().collect();
+
+        assert_eq!(lines.len(), 36);
+        assert_eq!(lines[0], "(4, 0) -> (9, 1): 6");
+        assert_eq!(lines[35], "(0, 11) -> (5, 11): 5");
+
+        let many_galaxies: Vec<Coordinate> =
+            (0..=DEBUG_PAIR_LIMIT as u64).map(|x| (x, 0)).collect();
+        assert_eq!(debug_pair_distances(&many_galaxies), None);
+    }
+
     #[test]
     fn test_calculate_distance() {
         let first_galaxy = (4, 0);
@@ -156,18 +287,35 @@ mod test_super {
     #[test]
     fn test_part_one_example() {
         let galaxy_map = test_data();
-        let expanded_map = expand(galaxy_map);
 
-        let part_one_example = part_one_solution(&expanded_map);
+        let part_one_example = part_one_solution(&galaxy_map);
         assert_eq!(part_one_example, 374);
     }
 
     #[test]
     fn test_part_one_solution() {
         let galaxy_map = process_input(include_str!("../data/puzzle_input.txt"));
-        let expanded_map = expand(galaxy_map);
 
-        let part_one_example = part_one_solution(&expanded_map);
+        let part_one_example = part_one_solution(&galaxy_map);
         assert_eq!(part_one_example, 9370588);
     }
+
+    #[test]
+    fn test_solve_larger_expansions() {
+        let galaxy_map = test_data();
+
+        assert_eq!(solve(&galaxy_map, 10), 1030);
+        assert_eq!(solve(&galaxy_map, 100), 8410);
+        // 1 leaves the map as it is, and 0 collapses the empty rows and columns entirely
+        assert_eq!(solve(&galaxy_map, 1), 292);
+        assert_eq!(solve(&galaxy_map, 0), 210);
+    }
+
+    #[test]
+    fn test_part_two_solution() {
+        let galaxy_map = process_input(include_str!("../data/puzzle_input.txt"));
+
+        let part_two_answer = part_two_solution(&galaxy_map);
+        assert_eq!(part_two_answer, 746207878188);
+    }
 }
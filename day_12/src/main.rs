@@ -1,35 +1,225 @@
-// TODO: Optimise Part One. Currently takes ~10s to complete with
-//       the brute force approach.
+mod nonogram;
 
-use std::str::FromStr;
+use std::{collections::HashMap, error::Error, fmt::Display, str::FromStr};
 
-fn main() {
+const DEFAULT_ARRANGEMENT_LIMIT: usize = 10;
+
+fn main() -> Result<(), Box<dyn Error>> {
     let input = include_str!("../data/puzzle_input.txt");
     let rows = process_input(input);
 
     let part_one_answer = part_one_solution(&rows);
     println!("Part One Answer is {part_one_answer}");
+
+    let part_two_answer = part_two_solution(&rows);
+    println!("Part Two Answer is {part_two_answer}");
+
+    // e.g. `cargo run -- --arrangements 12 5` to see the first 5 arrangements of row 12
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pos) = args.iter().position(|arg| arg == "--arrangements") {
+        let row_number = args
+            .get(pos + 1)
+            .ok_or("--arrangements needs a row number")?
+            .parse::<usize>()?;
+        let row = row_number
+            .checked_sub(1)
+            .and_then(|idx| rows.get(idx))
+            .ok_or(format!("There is no row {row_number}"))?;
+        let limit = match args.get(pos + 2) {
+            Some(limit) => limit.parse::<usize>()?,
+            None => DEFAULT_ARRANGEMENT_LIMIT,
+        };
+
+        println!("{}", describe_arrangements(row, limit));
+    }
+
+    // e.g. `cargo run -- --nonogram data/nonogram_test_input.txt`
+    if let Some(pos) = args.iter().position(|arg| arg == "--nonogram") {
+        let path = args.get(pos + 1).ok_or("--nonogram needs a clue file")?;
+        let (row_clues, column_clues) = nonogram::parse_clues(&std::fs::read_to_string(path)?)?;
+
+        match nonogram::solve(&row_clues, &column_clues) {
+            Some(solution) => println!("{}", nonogram::render(&solution)),
+            None => println!("The nonogram has no solution"),
+        }
+    }
+
+    Ok(())
 }
 
 fn part_one_solution(rows: &[Row]) -> u64 {
-    let mut valid_count = 0;
+    rows.iter().map(count_arrangements).sum()
+}
 
-    for row in rows {
-        let permutations = generate_all_permutations(row);
-        for permutation in &permutations {
-            if is_valid(permutation, &row.damaged_spring_grouping) {
-                valid_count += 1;
-            }
+fn part_two_solution(rows: &[Row]) -> u64 {
+    rows.iter()
+        .map(|row| count_arrangements(&row.unfold(5)))
+        .sum()
+}
+
+fn count_arrangements(row: &Row) -> u64 {
+    let mut memo: HashMap<(usize, usize, usize), u64> = HashMap::new();
+
+    count_from(row, 0, 0, 0, &mut memo)
+}
+
+// Counts the arrangements of springs[spring_idx..] given we're on group_idx and
+// have already seen run_length damaged springs in a row for it
+fn count_from(
+    row: &Row,
+    spring_idx: usize,
+    group_idx: usize,
+    run_length: usize,
+    memo: &mut HashMap<(usize, usize, usize), u64>,
+) -> u64 {
+    if spring_idx == row.springs.len() {
+        return is_finished(row, group_idx, run_length) as u64;
+    }
+
+    if let Some(count) = memo.get(&(spring_idx, group_idx, run_length)) {
+        return *count;
+    }
+
+    let count = next_states(row, spring_idx, group_idx, run_length)
+        .into_iter()
+        .map(|(_, next_group_idx, next_run_length)| {
+            count_from(row, spring_idx + 1, next_group_idx, next_run_length, memo)
+        })
+        .sum();
+
+    memo.insert((spring_idx, group_idx, run_length), count);
+
+    count
+}
+
+fn is_finished(row: &Row, group_idx: usize, run_length: usize) -> bool {
+    let groups = &row.damaged_spring_grouping;
+
+    let all_groups_closed = run_length == 0 && group_idx == groups.len();
+    let last_group_finishing =
+        group_idx + 1 == groups.len() && run_length == groups[group_idx] as usize;
+
+    all_groups_closed || last_group_finishing
+}
+
+// The ways the spring at spring_idx can be set without breaking the grouping,
+// along with the group_idx and run_length that follow from it
+fn next_states(
+    row: &Row,
+    spring_idx: usize,
+    group_idx: usize,
+    run_length: usize,
+) -> Vec<(SpringCondition, usize, usize)> {
+    let groups = &row.damaged_spring_grouping;
+    let spring = &row.springs[spring_idx];
+    let mut states = vec![];
+
+    if spring != &SpringCondition::Damaged {
+        if run_length == 0 {
+            states.push((SpringCondition::Operational, group_idx, 0));
+        } else if run_length == groups[group_idx] as usize {
+            states.push((SpringCondition::Operational, group_idx + 1, 0));
         }
     }
 
-    valid_count
+    if spring != &SpringCondition::Operational
+        && group_idx < groups.len()
+        && run_length < groups[group_idx] as usize
+    {
+        states.push((SpringCondition::Damaged, group_idx, run_length + 1));
+    }
+
+    states
 }
 
+fn arrangements(row: &Row) -> Arrangements<'_> {
+    Arrangements {
+        row,
+        stack: vec![(vec![], 0, 0)],
+    }
+}
+
+fn describe_arrangements(row: &Row, limit: usize) -> String {
+    let mut lines = vec![format!(
+        "{row} has {} arrangements",
+        count_arrangements(row)
+    )];
+
+    lines.extend(
+        arrangements(row)
+            .take(limit)
+            .map(|arrangement| render_springs(&arrangement)),
+    );
+
+    lines.join("\n")
+}
+
+fn render_springs(springs: &[SpringCondition]) -> String {
+    springs.iter().map(|spring| spring.to_string()).collect()
+}
+
+// Walks the same choices as the counter depth first, so a branch is dropped as
+// soon as it can't match the grouping rather than once it's fully built
+struct Arrangements<'a> {
+    row: &'a Row,
+    stack: Vec<(Vec<SpringCondition>, usize, usize)>,
+}
+
+impl Arrangements<'_> {
+    fn can_still_fit(&self, spring_idx: usize, group_idx: usize, run_length: usize) -> bool {
+        let remaining_groups = &self.row.damaged_spring_grouping
+            [group_idx.min(self.row.damaged_spring_grouping.len())..];
+        let damaged_needed = remaining_groups.iter().sum::<u32>() as usize - run_length;
+        let gaps_needed = remaining_groups.len().saturating_sub(1);
+
+        self.row.springs.len() - spring_idx >= damaged_needed + gaps_needed
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<SpringCondition>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((arrangement, group_idx, run_length)) = self.stack.pop() {
+            let spring_idx = arrangement.len();
+
+            if spring_idx == self.row.springs.len() {
+                if is_finished(self.row, group_idx, run_length) {
+                    return Some(arrangement);
+                }
+                continue;
+            }
+
+            // pushed in reverse so operational springs are tried first
+            for (condition, next_group_idx, next_run_length) in
+                next_states(self.row, spring_idx, group_idx, run_length)
+                    .into_iter()
+                    .rev()
+            {
+                if !self.can_still_fit(spring_idx + 1, next_group_idx, next_run_length) {
+                    continue;
+                }
+
+                let mut next_arrangement = arrangement.clone();
+                next_arrangement.push(condition);
+
+                self.stack
+                    .push((next_arrangement, next_group_idx, next_run_length));
+            }
+        }
+
+        None
+    }
+}
+
+// The brute force approach is far too slow for the real input, but it's kept
+// around to check the counter against
+#[cfg(test)]
 fn generate_all_permutations(row: &Row) -> Vec<Vec<SpringCondition>> {
     generate_permutation(row.springs.clone(), 0)
 }
 
+#[cfg(test)]
 fn generate_permutation(
     spring_conditions: Vec<SpringCondition>,
     idx: usize,
@@ -57,6 +247,7 @@ fn generate_permutation(
     vec![spring_conditions]
 }
 
+#[cfg(test)]
 fn is_valid(permutation: &Vec<SpringCondition>, count_grouping: &Vec<u32>) -> bool {
     let mut total: Vec<u32> = vec![];
 
@@ -81,7 +272,7 @@ fn is_valid(permutation: &Vec<SpringCondition>, count_grouping: &Vec<u32>) -> bo
     &total == count_grouping
 }
 
-#[derive(strum::EnumString, PartialEq, Debug, Clone)]
+#[derive(strum::EnumString, strum::Display, PartialEq, Debug, Clone)]
 enum SpringCondition {
     #[strum(serialize = ".")]
     Operational,
@@ -96,6 +287,41 @@ struct Row {
     damaged_spring_grouping: Vec<u32>,
 }
 
+impl Display for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let grouping: Vec<String> = self
+            .damaged_spring_grouping
+            .iter()
+            .map(|group| group.to_string())
+            .collect();
+
+        write!(
+            f,
+            "{} {}",
+            render_springs(&self.springs),
+            grouping.join(",")
+        )
+    }
+}
+
+impl Row {
+    fn unfold(&self, times: usize) -> Row {
+        let mut springs: Vec<SpringCondition> = vec![];
+
+        for copy in 0..times {
+            if copy > 0 {
+                springs.push(SpringCondition::Unknown);
+            }
+            springs.extend(self.springs.iter().cloned());
+        }
+
+        Row {
+            springs,
+            damaged_spring_grouping: self.damaged_spring_grouping.repeat(times),
+        }
+    }
+}
+
 fn process_input(input: &str) -> Vec<Row> {
     input
         .lines()
@@ -224,11 +450,124 @@ mod test_super {
         assert_eq!(part_one_solution(&test_data), 21);
     }
 
-    // Too slow to run in CI (~10s)
-    // #[test]
-    // fn test_part_one_solution() {
-    //     let puzzle_data = process_input(include_str!("../data/puzzle_input.txt"));
+    #[test]
+    fn test_count_arrangements() {
+        let rows = test_data();
+        let counts: Vec<u64> = rows.iter().map(count_arrangements).collect();
+
+        assert_eq!(counts, vec![1, 4, 1, 1, 4, 10]);
+    }
+
+    #[test]
+    fn test_count_arrangements_matches_brute_force() {
+        for row in test_data() {
+            let brute_force = generate_all_permutations(&row)
+                .iter()
+                .filter(|permutation| is_valid(permutation, &row.damaged_spring_grouping))
+                .count() as u64;
+
+            assert_eq!(count_arrangements(&row), brute_force);
+        }
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let rows = test_data();
+        let found: Vec<String> = arrangements(&rows[1])
+            .map(|arrangement| render_springs(&arrangement))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                "..#...#...###.",
+                "..#..#....###.",
+                ".#....#...###.",
+                ".#...#....###.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arrangements_match_counter() {
+        for row in test_data() {
+            let found: Vec<Vec<SpringCondition>> = arrangements(&row).collect();
+
+            assert_eq!(found.len() as u64, count_arrangements(&row));
+            assert!(found
+                .iter()
+                .all(|arrangement| is_valid(arrangement, &row.damaged_spring_grouping)));
+        }
+    }
+
+    #[test]
+    fn test_arrangements_are_lazy() {
+        let row = &test_data()[5].unfold(5);
+        let first_two: Vec<Vec<SpringCondition>> = arrangements(row).take(2).collect();
+
+        assert_eq!(first_two.len(), 2);
+        assert_ne!(first_two[0], first_two[1]);
+    }
+
+    #[test]
+    fn test_describe_arrangements() {
+        let rows = test_data();
+
+        assert_eq!(
+            describe_arrangements(&rows[5], 3),
+            "?###???????? 3,2,1 has 10 arrangements\n\
+             .###....##.#\n\
+             .###...##..#\n\
+             .###...##.#."
+        );
+    }
+
+    #[test]
+    fn test_unfold() {
+        let row = &process_input(".# 1")[0];
+        let unfolded = row.unfold(3);
 
-    //     assert_eq!(part_one_solution(&puzzle_data), 8419);
-    // }
+        assert_eq!(
+            unfolded.springs,
+            vec![
+                SpringCondition::Operational,
+                SpringCondition::Damaged,
+                SpringCondition::Unknown,
+                SpringCondition::Operational,
+                SpringCondition::Damaged,
+                SpringCondition::Unknown,
+                SpringCondition::Operational,
+                SpringCondition::Damaged,
+            ]
+        );
+        assert_eq!(unfolded.damaged_spring_grouping, vec![1, 1, 1]);
+
+        let unfolded_counts: Vec<u64> = test_data()
+            .iter()
+            .map(|row| count_arrangements(&row.unfold(5)))
+            .collect();
+
+        assert_eq!(unfolded_counts, vec![1, 16384, 1, 16, 2500, 506250]);
+    }
+
+    #[test]
+    fn test_part_one_solution() {
+        let puzzle_data = process_input(include_str!("../data/puzzle_input.txt"));
+
+        assert_eq!(part_one_solution(&puzzle_data), 8419);
+    }
+
+    #[test]
+    fn test_part_two_example() {
+        let test_data = test_data();
+
+        assert_eq!(part_two_solution(&test_data), 525152);
+    }
+
+    #[test]
+    fn test_part_two_solution() {
+        let puzzle_data = process_input(include_str!("../data/puzzle_input.txt"));
+
+        assert_eq!(part_two_solution(&puzzle_data), 160500973317706);
+    }
 }
@@ -0,0 +1,251 @@
+// A nonogram is a grid where every row and column is a day 12 row with nothing
+// known up front, so each line can be solved with the same arrangement counter
+
+use crate::{count_arrangements, render_springs, Row, SpringCondition};
+
+type Grid = Vec<Vec<SpringCondition>>;
+type Clues = Vec<Vec<u32>>;
+
+pub fn parse_clues(input: &str) -> Result<(Clues, Clues), String> {
+    let blocks: Vec<&str> = input.trim().split("\n\n").collect();
+
+    if blocks.len() != 2 {
+        return Err(format!(
+            "Expected a block of row clues and a block of column clues, got {} blocks",
+            blocks.len()
+        ));
+    }
+
+    let row_clues = parse_clue_block(blocks[0])?;
+    let column_clues = parse_clue_block(blocks[1])?;
+
+    Ok((row_clues, column_clues))
+}
+
+fn parse_clue_block(block: &str) -> Result<Clues, String> {
+    block
+        .lines()
+        .map(|line| {
+            line.split(',')
+                .map(|clue| {
+                    clue.trim()
+                        .parse::<u32>()
+                        .map_err(|_| format!("Could not parse clue: {line}"))
+                })
+                // an empty line is written as 0, which is just no groups at all
+                .filter(|clue| clue != &Ok(0))
+                .collect()
+        })
+        .collect()
+}
+
+pub fn solve(row_clues: &[Vec<u32>], column_clues: &[Vec<u32>]) -> Option<Grid> {
+    let grid = vec![vec![SpringCondition::Unknown; column_clues.len()]; row_clues.len()];
+
+    solve_grid(grid, row_clues, column_clues)
+}
+
+pub fn render(grid: &Grid) -> String {
+    grid.iter()
+        .map(|row| render_springs(row))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn solve_grid(mut grid: Grid, row_clues: &[Vec<u32>], column_clues: &[Vec<u32>]) -> Option<Grid> {
+    propagate(&mut grid, row_clues, column_clues)?;
+
+    let unknown = grid.iter().enumerate().find_map(|(y, row)| {
+        row.iter()
+            .position(|cell| cell == &SpringCondition::Unknown)
+            .map(|x| (x, y))
+    });
+
+    // every line had at least one arrangement once propagation settled,
+    // so a grid with nothing left to guess is a solution
+    let Some((x, y)) = unknown else {
+        return Some(grid);
+    };
+
+    [SpringCondition::Damaged, SpringCondition::Operational]
+        .into_iter()
+        .find_map(|guess| {
+            let mut guessed_grid = grid.clone();
+            guessed_grid[y][x] = guess;
+
+            solve_grid(guessed_grid, row_clues, column_clues)
+        })
+}
+
+// Solves lines until nothing else changes, returning None if any line can't be satisfied
+fn propagate(grid: &mut Grid, row_clues: &[Vec<u32>], column_clues: &[Vec<u32>]) -> Option<()> {
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        for (y, clue) in row_clues.iter().enumerate() {
+            let solved = solve_line(grid[y].clone(), clue)?;
+
+            if solved != grid[y] {
+                grid[y] = solved;
+                changed = true;
+            }
+        }
+
+        for (x, clue) in column_clues.iter().enumerate() {
+            let column: Vec<SpringCondition> = grid.iter().map(|row| row[x].clone()).collect();
+            let solved = solve_line(column.clone(), clue)?;
+
+            if solved != column {
+                for (row, cell) in grid.iter_mut().zip(solved) {
+                    row[x] = cell;
+                }
+                changed = true;
+            }
+        }
+    }
+
+    Some(())
+}
+
+// A cell is only filled in once every arrangement of the line agrees on it
+fn solve_line(line: Vec<SpringCondition>, clue: &[u32]) -> Option<Vec<SpringCondition>> {
+    let mut row = Row {
+        springs: line,
+        damaged_spring_grouping: clue.to_vec(),
+    };
+
+    if count_arrangements(&row) == 0 {
+        return None;
+    }
+
+    for idx in 0..row.springs.len() {
+        if row.springs[idx] != SpringCondition::Unknown {
+            continue;
+        }
+
+        row.springs[idx] = SpringCondition::Damaged;
+        let can_be_damaged = count_arrangements(&row) > 0;
+
+        row.springs[idx] = SpringCondition::Operational;
+        let can_be_operational = count_arrangements(&row) > 0;
+
+        row.springs[idx] = match (can_be_damaged, can_be_operational) {
+            (true, false) => SpringCondition::Damaged,
+            (false, true) => SpringCondition::Operational,
+            _ => SpringCondition::Unknown,
+        };
+    }
+
+    Some(row.springs)
+}
+
+#[cfg(test)]
+mod test_super {
+    use super::*;
+
+    fn line(springs: &str) -> Vec<SpringCondition> {
+        crate::process_input(&format!("{springs} 1"))[0]
+            .springs
+            .clone()
+    }
+
+    fn clues_for(grid: &Grid) -> (Clues, Clues) {
+        let groups = |cells: Vec<&SpringCondition>| -> Vec<u32> {
+            let rendered: String = cells.iter().map(|cell| cell.to_string()).collect();
+            rendered
+                .split('.')
+                .filter(|group| !group.is_empty())
+                .map(|group| group.len() as u32)
+                .collect()
+        };
+
+        let row_clues = grid
+            .iter()
+            .map(|row| groups(row.iter().collect()))
+            .collect();
+        let column_clues = (0..grid[0].len())
+            .map(|x| groups(grid.iter().map(|row| &row[x]).collect()))
+            .collect();
+
+        (row_clues, column_clues)
+    }
+
+    #[test]
+    fn test_parse_clues() {
+        let (row_clues, column_clues) =
+            parse_clues(include_str!("../data/nonogram_test_input.txt")).unwrap();
+
+        assert_eq!(
+            row_clues,
+            vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]]
+        );
+        assert_eq!(
+            column_clues,
+            vec![vec![2], vec![4], vec![4], vec![4], vec![2]]
+        );
+
+        let (row_clues, _) = parse_clues("0\n1\n\n1\n0").unwrap();
+        assert_eq!(row_clues, vec![vec![], vec![1]]);
+
+        assert!(parse_clues("1\n1").is_err());
+        assert!(parse_clues("1\nx\n\n1\n1").is_err());
+    }
+
+    #[test]
+    fn test_solve_line() {
+        assert_eq!(solve_line(line("?????"), &[4]), Some(line("?###?")));
+        assert_eq!(solve_line(line("?????"), &[2, 2]), Some(line("##.##")));
+        assert_eq!(solve_line(line("?????"), &[1]), Some(line("?????")));
+        assert_eq!(solve_line(line("?#???"), &[]), None);
+        assert_eq!(solve_line(line("??#??"), &[1, 1, 1]), Some(line("#.#.#")));
+    }
+
+    #[test]
+    fn test_solve_heart() {
+        let (row_clues, column_clues) =
+            parse_clues(include_str!("../data/nonogram_test_input.txt")).unwrap();
+        let solution = solve(&row_clues, &column_clues).unwrap();
+
+        assert_eq!(
+            render(&solution),
+            ".#.#.\n\
+             #####\n\
+             #####\n\
+             .###.\n\
+             ..#.."
+        );
+    }
+
+    #[test]
+    fn test_solve_needs_backtracking() {
+        // either diagonal fits, so line solving alone can't fill anything in
+        let row_clues = vec![vec![1], vec![1]];
+        let column_clues = vec![vec![1], vec![1]];
+        let solution = solve(&row_clues, &column_clues).unwrap();
+
+        assert_eq!(render(&solution), "#.\n.#");
+    }
+
+    #[test]
+    fn test_solve_matches_clues() {
+        let picture = crate::process_input("#..##.#. 1\n.###..## 1\n##...#.. 1\n..##.### 1")
+            .into_iter()
+            .map(|row| row.springs)
+            .collect::<Grid>();
+        let (row_clues, column_clues) = clues_for(&picture);
+
+        let solution = solve(&row_clues, &column_clues).unwrap();
+
+        assert_eq!(clues_for(&solution), (row_clues, column_clues));
+    }
+
+    #[test]
+    fn test_solve_impossible() {
+        let row_clues = vec![vec![2], vec![]];
+        let column_clues = vec![vec![], vec![1]];
+
+        assert_eq!(solve(&row_clues, &column_clues), None);
+    }
+}
@@ -1,13 +1,19 @@
-// TODO: pull generic logic out of find_reflection_point
-use ndarray::{Array2, Axis};
+use std::{error::Error, fmt::Display};
 
-fn main() {
+use ndarray::{Array2, ArrayView2};
+
+fn main() -> Result<(), Box<dyn Error>> {
     let input = include_str!("../data/puzzle_input.txt");
 
     let terrains = process_input(input);
 
-    let part_one_answer = part_one_solution(&terrains);
+    let part_one_answer = part_one_solution(&terrains)?;
     println!("Part One answer is {part_one_answer}");
+
+    let part_two_answer = part_two_solution(&terrains)?;
+    println!("Part Two answer is {part_two_answer}");
+
+    Ok(())
 }
 
 #[derive(PartialEq, Debug)]
@@ -16,69 +22,93 @@ enum ReflectionType {
     Horizontal,
 }
 
-fn part_one_solution(terrains: &[Array2<char>]) -> u64 {
-    terrains.iter().fold(0, |acc, terrain| {
-        let (reflection_point, reflection_type) = find_reflection_point(terrain);
-
-        acc + (match reflection_type {
-            ReflectionType::Vertical => reflection_point,
-            ReflectionType::Horizontal => reflection_point * 100,
-        } as u64)
-    })
+fn part_one_solution(terrains: &[Array2<char>]) -> Result<u64, ReflectionError> {
+    terrains
+        .iter()
+        .try_fold(0, |acc, terrain| Ok(acc + summarise(terrain, 0)?))
 }
 
-fn find_reflection_point(terrain: &Array2<char>) -> (u32, ReflectionType) {
-    // vertical
-    let column_length = terrain.len_of(Axis(1));
-    for y_idx in 0..(column_length - 1) {
-        let mut valid = true;
-        let mut less_step = 0;
-        let mut more_step = 1;
-
-        while valid {
-            if y_idx.checked_sub(less_step).is_none() || (y_idx + more_step) >= column_length {
-                break;
-            }
+fn part_two_solution(terrains: &[Array2<char>]) -> Result<u64, ReflectionError> {
+    terrains
+        .iter()
+        .try_fold(0, |acc, terrain| Ok(acc + summarise(terrain, 1)?))
+}
 
-            let left = terrain.column(y_idx - less_step);
-            let right = terrain.column(y_idx + more_step);
+fn summarise(terrain: &Array2<char>, smudges: usize) -> Result<u64, ReflectionError> {
+    let reflection_points = find_reflection_points(terrain, smudges)?;
+    let (reflection_point, reflection_type) = &reflection_points[0];
 
-            valid = left == right;
-            less_step += 1;
-            more_step += 1;
-        }
+    Ok((match reflection_type {
+        ReflectionType::Vertical => *reflection_point,
+        ReflectionType::Horizontal => reflection_point * 100,
+    }) as u64)
+}
 
-        if valid {
-            return ((y_idx + 1) as u32, ReflectionType::Vertical);
-        }
+fn find_reflection_points(
+    terrain: &Array2<char>,
+    smudges: usize,
+) -> Result<Vec<(u32, ReflectionType)>, ReflectionError> {
+    // both directions get encoded as bitmasks, so both have to fit in one
+    let (row_count, column_count) = terrain.dim();
+    if row_count.max(column_count) > u64::BITS as usize {
+        return Err(ReflectionError::TooLarge {
+            rows: row_count,
+            columns: column_count,
+        });
     }
 
-    // horizontal
-    let row_amount = terrain.len_of(Axis(0));
-    for x_idx in 0..(row_amount - 1) {
-        let mut valid = true;
-        let mut less_step = 0;
-        let mut more_step = 1;
+    // the columns of the terrain are the rows of its transpose, so both
+    // directions can go through the same row based search
+    let columns = encode_rows(terrain.t());
+    let rows = encode_rows(terrain.view());
 
-        while valid {
-            if x_idx.checked_sub(less_step).is_none() || (x_idx + more_step) >= row_amount {
-                break;
-            }
-
-            let left = terrain.row(x_idx - less_step);
-            let right = terrain.row(x_idx + more_step);
+    let vertical = find_reflections(&columns, smudges)
+        .into_iter()
+        .map(|point| (point, ReflectionType::Vertical));
+    let horizontal = find_reflections(&rows, smudges)
+        .into_iter()
+        .map(|point| (point, ReflectionType::Horizontal));
 
-            valid = left == right;
-            less_step += 1;
-            more_step += 1;
-        }
+    let reflection_points: Vec<(u32, ReflectionType)> = vertical.chain(horizontal).collect();
 
-        if valid {
-            return ((x_idx + 1) as u32, ReflectionType::Horizontal);
-        }
+    if reflection_points.is_empty() {
+        Err(ReflectionError::NoReflection { smudges })
+    } else {
+        Ok(reflection_points)
     }
+}
+
+// Each row becomes a bitmask with rocks set, so the number of cells that differ
+// between two rows is just the set bits of their XOR. Rows longer than 64 cells
+// would lose their first cells, so the caller has to check the size first
+fn encode_rows(terrain: ArrayView2<char>) -> Vec<u64> {
+    terrain
+        .rows()
+        .into_iter()
+        .map(|row| {
+            row.iter()
+                .fold(0, |mask, cell| (mask << 1) | (cell == &'#') as u64)
+        })
+        .collect()
+}
+
+// A reflection only counts if exactly `smudges` cells across the whole fold
+// don't match their mirrored cell
+fn find_reflections(lines: &[u64], smudges: usize) -> Vec<u32> {
+    (1..lines.len())
+        .filter(|&split| {
+            let before = lines[..split].iter().rev();
+            let after = lines[split..].iter();
 
-    panic!("could not find reflection point")
+            let mismatches: u32 = before
+                .zip(after)
+                .map(|(first, other)| (first ^ other).count_ones())
+                .sum();
+
+            mismatches as usize == smudges
+        })
+        .map(|split| split as u32)
+        .collect()
 }
 
 fn process_input(input: &str) -> Vec<Array2<char>> {
@@ -98,6 +128,32 @@ fn process_input(input: &str) -> Vec<Array2<char>> {
         .collect()
 }
 
+#[derive(Debug, PartialEq)]
+enum ReflectionError {
+    NoReflection { smudges: usize },
+    TooLarge { rows: usize, columns: usize },
+}
+
+impl Display for ReflectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoReflection { smudges } => {
+                write!(
+                    f,
+                    "could not find a reflection point with {smudges} smudges"
+                )
+            }
+            Self::TooLarge { rows, columns } => write!(
+                f,
+                "terrain is {rows} rows by {columns} columns, only up to {} of each are supported",
+                u64::BITS
+            ),
+        }
+    }
+}
+
+impl Error for ReflectionError {}
+
 #[cfg(test)]
 mod test_super {
     use super::*;
@@ -125,22 +181,105 @@ mod test_super {
     }
 
     #[test]
-    fn test_find_reflection_point() {
+    fn test_find_reflection_points() {
+        let test_terrains = test_data();
+
+        let reflection_points = find_reflection_points(&test_terrains[0], 0).unwrap();
+        assert_eq!(reflection_points, vec![(5, ReflectionType::Vertical)]);
+
+        let reflection_points = find_reflection_points(&test_terrains[1], 0).unwrap();
+        assert_eq!(reflection_points, vec![(4, ReflectionType::Horizontal)]);
+    }
+
+    #[test]
+    fn test_find_reflection_points_with_smudge() {
         let test_terrains = test_data();
 
-        let (point, reflection_type) = find_reflection_point(&test_terrains[0]);
-        assert_eq!(point, 5);
-        assert_eq!(reflection_type, ReflectionType::Vertical);
+        let reflection_points = find_reflection_points(&test_terrains[0], 1).unwrap();
+        assert_eq!(reflection_points, vec![(3, ReflectionType::Horizontal)]);
 
-        let (point, reflection_type) = find_reflection_point(&test_terrains[1]);
-        assert_eq!(point, 4);
-        assert_eq!(reflection_type, ReflectionType::Horizontal);
+        let reflection_points = find_reflection_points(&test_terrains[1], 1).unwrap();
+        assert_eq!(reflection_points, vec![(1, ReflectionType::Horizontal)]);
+    }
+
+    #[test]
+    fn test_find_reflection_points_returns_every_candidate() {
+        let terrain = &process_input("....\n....")[0];
+
+        assert_eq!(
+            find_reflection_points(terrain, 0).unwrap(),
+            vec![
+                (1, ReflectionType::Vertical),
+                (2, ReflectionType::Vertical),
+                (3, ReflectionType::Vertical),
+                (1, ReflectionType::Horizontal)
+            ]
+        );
+
+        let terrain = &process_input("#...\n....")[0];
+
+        assert_eq!(
+            find_reflection_points(terrain, 1).unwrap(),
+            vec![
+                (1, ReflectionType::Vertical),
+                (2, ReflectionType::Vertical),
+                (1, ReflectionType::Horizontal)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_reflection_points_errors() {
+        let terrain = &process_input("#.\n..")[0];
+        assert_eq!(
+            find_reflection_points(terrain, 0),
+            Err(ReflectionError::NoReflection { smudges: 0 })
+        );
+
+        let terrain = &process_input(&".".repeat(65))[0];
+        assert_eq!(
+            find_reflection_points(terrain, 0),
+            Err(ReflectionError::TooLarge {
+                rows: 1,
+                columns: 65
+            })
+        );
+
+        let terrain = &process_input(&".\n".repeat(65))[0];
+        assert_eq!(
+            find_reflection_points(terrain, 0),
+            Err(ReflectionError::TooLarge {
+                rows: 65,
+                columns: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_encode_rows() {
+        let test_terrains = test_data();
+        let rows = encode_rows(test_terrains[0].view());
+
+        assert_eq!(rows[0], 0b101100110);
+        assert_eq!(rows.len(), 7);
+
+        let columns = encode_rows(test_terrains[0].t());
+
+        assert_eq!(columns[0], 0b1011001);
+        assert_eq!(columns.len(), 9);
+    }
+
+    #[test]
+    fn test_find_reflections() {
+        assert_eq!(find_reflections(&[0b01, 0b10, 0b10, 0b01], 0), vec![2]);
+        assert_eq!(find_reflections(&[0b01, 0b10, 0b10, 0b01], 2), vec![1, 3]);
+        assert_eq!(find_reflections(&[0b11, 0b01, 0b01], 1), vec![1]);
     }
 
     #[test]
     fn test_part_one_example() {
         let test_terrains = test_data();
-        let part_one_example = part_one_solution(&test_terrains);
+        let part_one_example = part_one_solution(&test_terrains).unwrap();
 
         assert_eq!(part_one_example, 405);
     }
@@ -149,8 +288,25 @@ mod test_super {
     fn test_part_one_answer() {
         let terrains = process_input(include_str!("../data/puzzle_input.txt"));
 
-        let part_one_answer = part_one_solution(&terrains);
+        let part_one_answer = part_one_solution(&terrains).unwrap();
 
         assert_eq!(part_one_answer, 33735);
     }
+
+    #[test]
+    fn test_part_two_example() {
+        let test_terrains = test_data();
+        let part_two_example = part_two_solution(&test_terrains).unwrap();
+
+        assert_eq!(part_two_example, 400);
+    }
+
+    #[test]
+    fn test_part_two_answer() {
+        let terrains = process_input(include_str!("../data/puzzle_input.txt"));
+
+        let part_two_answer = part_two_solution(&terrains).unwrap();
+
+        assert_eq!(part_two_answer, 38063);
+    }
 }
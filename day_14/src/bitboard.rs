@@ -0,0 +1,244 @@
+use std::hash::{Hash, Hasher};
+
+use ndarray::Array2;
+
+use crate::{Direction, Platform, CUBE_ROCK, ROUNDED_ROCK};
+
+type Lanes = Vec<u128>;
+
+// Each row and column is a bitset with bit i set when there's a rock at index i
+// along it. North/south tilts work on the columns and west/east on the rows,
+// with the other set rebuilt afterwards so both stay in step
+#[derive(Clone, Debug)]
+pub struct BitPlatform {
+    width: usize,
+    height: usize,
+    rounded_rows: Lanes,
+    rounded_columns: Lanes,
+    cube_rows: Lanes,
+    cube_columns: Lanes,
+}
+
+impl TryFrom<&Array2<char>> for BitPlatform {
+    type Error = String;
+
+    fn try_from(platform: &Array2<char>) -> Result<Self, Self::Error> {
+        let (height, width) = platform.dim();
+
+        if width > u128::BITS as usize || height > u128::BITS as usize {
+            return Err(format!(
+                "Platform is {width}x{height}, bitboards only go up to {}x{}",
+                u128::BITS,
+                u128::BITS
+            ));
+        }
+
+        let mut rounded_rows = vec![0; height];
+        let mut cube_rows = vec![0; height];
+
+        for ((y, x), position) in platform.indexed_iter() {
+            if position == &ROUNDED_ROCK {
+                rounded_rows[y] |= 1 << x;
+            } else if position == &CUBE_ROCK {
+                cube_rows[y] |= 1 << x;
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            rounded_columns: transpose(&rounded_rows, width),
+            cube_columns: transpose(&cube_rows, width),
+            rounded_rows,
+            cube_rows,
+        })
+    }
+}
+
+impl Platform for BitPlatform {
+    fn tilt(&mut self, direction: Direction) {
+        match direction {
+            Direction::North | Direction::South => {
+                for (rounded, cube) in self.rounded_columns.iter_mut().zip(&self.cube_columns) {
+                    *rounded = tilt_lane(*rounded, *cube, self.height, direction);
+                }
+                self.rounded_rows = transpose(&self.rounded_columns, self.height);
+            }
+            Direction::West | Direction::East => {
+                for (rounded, cube) in self.rounded_rows.iter_mut().zip(&self.cube_rows) {
+                    *rounded = tilt_lane(*rounded, *cube, self.width, direction);
+                }
+                self.rounded_columns = transpose(&self.rounded_rows, self.width);
+            }
+        }
+    }
+
+    fn load(&self) -> u32 {
+        self.rounded_rows
+            .iter()
+            .enumerate()
+            .map(|(y, row)| row.count_ones() * (self.height - y) as u32)
+            .sum()
+    }
+}
+
+// Only states of the same platform ever get compared, and neither its size nor
+// its cube rocks change, so the rounded rocks are all that tell two states apart
+impl PartialEq for BitPlatform {
+    fn eq(&self, other: &Self) -> bool {
+        self.rounded_rows == other.rounded_rows
+    }
+}
+
+impl Eq for BitPlatform {}
+
+impl Hash for BitPlatform {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rounded_rows.hash(state);
+    }
+}
+
+// Splits the lane into the stretches between cube rocks and packs each stretch's
+// rounded rocks against the end they're rolling towards
+fn tilt_lane(rounded: u128, cube: u128, length: usize, direction: Direction) -> u128 {
+    let towards_start = matches!(direction, Direction::North | Direction::West);
+    let mut tilted = 0;
+    let mut segment_start = 0;
+
+    while segment_start < length {
+        let remaining_cubes = cube >> segment_start;
+        let segment_end = if remaining_cubes == 0 {
+            length
+        } else {
+            segment_start + remaining_cubes.trailing_zeros() as usize
+        };
+
+        let segment_mask = mask(segment_end - segment_start) << segment_start;
+        let rock_count = (rounded & segment_mask).count_ones() as usize;
+
+        // an empty stretch running to the far edge of a full 128 wide lane would
+        // otherwise shift by the whole width of the u128
+        if rock_count > 0 {
+            tilted |= if towards_start {
+                mask(rock_count) << segment_start
+            } else {
+                mask(rock_count) << (segment_end - rock_count)
+            };
+        }
+
+        segment_start = segment_end + 1;
+    }
+
+    tilted
+}
+
+fn mask(length: usize) -> u128 {
+    if length >= u128::BITS as usize {
+        u128::MAX
+    } else {
+        (1 << length) - 1
+    }
+}
+
+fn transpose(lanes: &[u128], length: usize) -> Lanes {
+    (0..length)
+        .map(|idx| {
+            lanes.iter().enumerate().fold(0, |acc, (lane_idx, lane)| {
+                acc | ((lane >> idx) & 1) << lane_idx
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_super {
+    use super::*;
+    use crate::{process_input, spin_cycle};
+
+    #[test]
+    fn test_tilt_lane() {
+        // bit 0 is the start of the lane, so these read right to left
+        assert_eq!(
+            tilt_lane(0b0100_1010, 0b0001_0000, 8, Direction::North),
+            0b0010_0011
+        );
+        assert_eq!(
+            tilt_lane(0b0100_1010, 0b0001_0000, 8, Direction::South),
+            0b1000_1100
+        );
+        assert_eq!(tilt_lane(0b101, 0, 3, Direction::West), 0b011);
+        assert_eq!(tilt_lane(0b101, 0, 3, Direction::East), 0b110);
+        assert_eq!(tilt_lane(0b1, 0b10, 2, Direction::East), 0b1);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let rows = vec![0b01, 0b11, 0b10];
+        let columns = transpose(&rows, 2);
+
+        assert_eq!(columns, vec![0b011, 0b110]);
+        assert_eq!(transpose(&columns, 3), rows);
+    }
+
+    #[test]
+    fn test_matches_char_grid() {
+        let mut platform = process_input(include_str!("../data/test_input.txt"));
+        let mut bit_platform = BitPlatform::try_from(&platform).unwrap();
+
+        for direction in [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ] {
+            platform.tilt(direction);
+            bit_platform.tilt(direction);
+
+            assert_eq!(bit_platform, BitPlatform::try_from(&platform).unwrap());
+            assert_eq!(bit_platform.load(), platform.load());
+        }
+
+        for _ in 0..5 {
+            spin_cycle(&mut platform);
+            spin_cycle(&mut bit_platform);
+
+            assert_eq!(bit_platform, BitPlatform::try_from(&platform).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_too_large() {
+        let platform = process_input(&".".repeat(129));
+        assert!(BitPlatform::try_from(&platform).is_err());
+
+        let platform = process_input(&".\n".repeat(129));
+        assert!(BitPlatform::try_from(&platform).is_err());
+    }
+
+    #[test]
+    fn test_largest() {
+        // a rock in the first lane so the others run empty right to the far edge
+        let wide = format!("O{}", ".".repeat(127));
+        let tall = format!("O\n{}", ".\n".repeat(127));
+
+        for (input, direction) in [(wide, Direction::East), (tall, Direction::South)] {
+            let mut platform = process_input(&input);
+            let mut bit_platform = BitPlatform::try_from(&platform).unwrap();
+
+            for direction in [direction, Direction::North, Direction::West, direction] {
+                platform.tilt(direction);
+                bit_platform.tilt(direction);
+
+                assert_eq!(bit_platform, BitPlatform::try_from(&platform).unwrap());
+            }
+        }
+
+        let mut empty = BitPlatform::try_from(&process_input(&".".repeat(128))).unwrap();
+        empty.tilt(Direction::East);
+        assert_eq!(empty.load(), 0);
+
+        let mut empty = BitPlatform::try_from(&process_input(&".\n".repeat(128))).unwrap();
+        empty.tilt(Direction::South);
+        assert_eq!(empty.load(), 0);
+    }
+}
@@ -1,15 +1,79 @@
-// TODO: Fix tilt_column method signature
-use ndarray::{Array2, ArrayBase, Dim, ViewRepr};
+mod bitboard;
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+use bitboard::BitPlatform;
+use ndarray::{s, Array2, ArrayView1, ArrayViewMut1};
 
 const ROUNDED_ROCK: char = 'O';
 const CUBE_ROCK: char = '#';
+const SPIN_CYCLES: usize = 1_000_000_000;
+
+const BENCH_RUNS: u32 = 20;
 
-fn main() {
+fn main() -> Result<(), Box<dyn Error>> {
     let input = include_str!("../data/puzzle_input.txt");
     let platform = process_input(input);
+    let args: Vec<String> = std::env::args().collect();
 
     let part_one_answer = part_one_solution(&platform);
     println!("Part one answer is: {part_one_answer}");
+
+    let part_two_answer = if args.iter().any(|arg| arg == "--bitboard") {
+        part_two_solution(&BitPlatform::try_from(&platform)?, SPIN_CYCLES)
+    } else {
+        part_two_solution(&platform, SPIN_CYCLES)
+    };
+    println!("Part two answer is: {part_two_answer}");
+
+    if args.iter().any(|arg| arg == "--bench") {
+        let bit_platform = BitPlatform::try_from(&platform)?;
+
+        let (load, elapsed) = bench(&platform);
+        println!("Char grid: {elapsed:?} per run (load {load})");
+
+        let (load, elapsed) = bench(&bit_platform);
+        println!("Bitboard: {elapsed:?} per run (load {load})");
+    }
+
+    Ok(())
+}
+
+// Times the full part two solve for either representation, returning the answer
+// alongside so a fast but wrong platform stands out. Every day here is a single
+// binary with nothing for a benches/ harness to link against, so this stands in
+// for one. Run with --release, the debug build numbers aren't worth comparing
+fn bench<P: Platform>(platform: &P) -> (u32, Duration) {
+    let start = Instant::now();
+    let mut load = 0;
+
+    for _ in 0..BENCH_RUNS {
+        load = part_two_solution(platform, SPIN_CYCLES);
+    }
+
+    (load, start.elapsed() / BENCH_RUNS)
+}
+
+// Anything that can be tilted and weighed can be spun, the char grid is the
+// simplest version and the bitboard is the fast one
+trait Platform: Clone + Eq + Hash {
+    fn tilt(&mut self, direction: Direction);
+    fn load(&self) -> u32;
+}
+
+impl Platform for Array2<char> {
+    fn tilt(&mut self, direction: Direction) {
+        tilt(self, direction);
+    }
+
+    fn load(&self) -> u32 {
+        calculate_load(self)
+    }
 }
 
 fn part_one_solution(platform: &Array2<char>) -> u32 {
@@ -21,21 +85,91 @@ fn part_one_solution(platform: &Array2<char>) -> u32 {
     })
 }
 
-fn tilt_column(column: &ArrayBase<ViewRepr<&char>, Dim<[usize; 1]>>) -> Vec<char> {
-    let mut tilted_column: Vec<char> = column.to_vec();
+fn part_two_solution<P: Platform>(platform: &P, cycles: usize) -> u32 {
+    let mut platform = platform.clone();
+    let mut seen: HashMap<P, usize> = HashMap::new();
+    let mut loads: Vec<u32> = vec![];
+
+    for cycle in 0..cycles {
+        if let Some(cycle_start) = seen.get(&platform) {
+            // the platform is back in a state it's been in before, so from here on
+            // it just repeats the same states over and over
+            let cycle_length = cycle - cycle_start;
+            let remaining = (cycles - cycle_start) % cycle_length;
+
+            return loads[cycle_start + remaining];
+        }
+
+        seen.insert(platform.clone(), cycle);
+        loads.push(platform.load());
+
+        spin_cycle(&mut platform);
+    }
+
+    platform.load()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    North,
+    West,
+    South,
+    East,
+}
+
+fn spin_cycle<P: Platform>(platform: &mut P) {
+    for direction in [
+        Direction::North,
+        Direction::West,
+        Direction::South,
+        Direction::East,
+    ] {
+        platform.tilt(direction);
+    }
+}
+
+// Every direction is a tilt towards the start of a lane, the lanes are just
+// the columns or rows, reversed when rolling towards the far edge
+fn tilt(platform: &mut Array2<char>, direction: Direction) {
+    match direction {
+        Direction::North => platform.columns_mut().into_iter().for_each(tilt_lane),
+        Direction::South => platform
+            .columns_mut()
+            .into_iter()
+            .for_each(|mut column| tilt_lane(column.slice_mut(s![..;-1]))),
+        Direction::West => platform.rows_mut().into_iter().for_each(tilt_lane),
+        Direction::East => platform
+            .rows_mut()
+            .into_iter()
+            .for_each(|mut row| tilt_lane(row.slice_mut(s![..;-1]))),
+    }
+}
+
+fn tilt_lane(mut lane: ArrayViewMut1<char>) {
     let mut next_spot = 0;
 
-    for i in 0..tilted_column.len() {
-        let current_spot = tilted_column[i];
+    for i in 0..lane.len() {
+        let current_spot = lane[i];
         if current_spot == ROUNDED_ROCK {
-            tilted_column.swap(next_spot, i);
+            lane.swap(next_spot, i);
             next_spot += 1;
         } else if current_spot == CUBE_ROCK {
             next_spot = i + 1;
         }
     }
+}
 
-    tilted_column
+fn tilt_column(column: &ArrayView1<char>) -> Vec<char> {
+    let mut tilted_column = column.to_owned();
+    tilt_lane(tilted_column.view_mut());
+
+    tilted_column.to_vec()
+}
+
+fn calculate_load(platform: &Array2<char>) -> u32 {
+    platform.columns().into_iter().fold(0, |acc, column| {
+        acc + calculate_column_load(&column.to_vec())
+    })
 }
 
 fn calculate_column_load(column: &[char]) -> u32 {
@@ -55,7 +189,7 @@ fn process_input(input: &str) -> Array2<char> {
     let col_length = positions.len();
 
     Array2::from_shape_vec(
-        (row_length, col_length),
+        (col_length, row_length),
         positions.iter().flatten().cloned().collect(),
     )
     .unwrap()
@@ -123,4 +257,103 @@ mod test_super {
 
         assert_eq!(part_one_answer, 108826);
     }
+
+    #[test]
+    fn test_tilt() {
+        let mut platform = process_input("O.#O\n.O.O\nO..#\n.#O.");
+
+        tilt(&mut platform, Direction::North);
+        assert_eq!(platform, process_input("OO#O\nO.OO\n...#\n.#.."));
+
+        tilt(&mut platform, Direction::East);
+        assert_eq!(platform, process_input("OO#O\n.OOO\n...#\n.#.."));
+
+        tilt(&mut platform, Direction::South);
+        assert_eq!(platform, process_input("..#O\n.O.O\n.O.#\nO#O."));
+
+        tilt(&mut platform, Direction::West);
+        assert_eq!(platform, process_input("..#O\nOO..\nO..#\nO#O."));
+    }
+
+    #[test]
+    fn test_spin_cycle() {
+        let mut platform = test_data();
+
+        spin_cycle(&mut platform);
+        assert_eq!(
+            platform,
+            process_input(
+                ".....#....\n\
+                 ....#...O#\n\
+                 ...OO##...\n\
+                 .OO#......\n\
+                 .....OOO#.\n\
+                 .O#...O#.#\n\
+                 ....O#....\n\
+                 ......OOOO\n\
+                 #...O###..\n\
+                 #..OO#...."
+            )
+        );
+
+        spin_cycle(&mut platform);
+        spin_cycle(&mut platform);
+        assert_eq!(
+            platform,
+            process_input(
+                ".....#....\n\
+                 ....#...O#\n\
+                 .....##...\n\
+                 ..O#......\n\
+                 .....OOO#.\n\
+                 .O#...O#.#\n\
+                 ....O#...O\n\
+                 .......OOO\n\
+                 #...O###.O\n\
+                 #.OOO#...O"
+            )
+        );
+    }
+
+    #[test]
+    fn test_calculate_load() {
+        let mut platform = test_data();
+        tilt(&mut platform, Direction::North);
+
+        assert_eq!(calculate_load(&platform), 136);
+    }
+
+    #[test]
+    fn test_part_two_example() {
+        let test_platform = test_data();
+
+        assert_eq!(part_two_solution(&test_platform, SPIN_CYCLES), 64);
+        assert_eq!(part_two_solution(&test_platform, 0), 104);
+
+        let bit_platform = BitPlatform::try_from(&test_platform).unwrap();
+
+        assert_eq!(part_two_solution(&bit_platform, SPIN_CYCLES), 64);
+        assert_eq!(part_two_solution(&bit_platform, 0), 104);
+    }
+
+    #[test]
+    fn test_part_two_answer() {
+        let platform = process_input(include_str!("../data/puzzle_input.txt"));
+
+        let part_two_answer = part_two_solution(&platform, SPIN_CYCLES);
+        assert_eq!(part_two_answer, 99291);
+
+        let bit_platform = BitPlatform::try_from(&platform).unwrap();
+        let part_two_answer = part_two_solution(&bit_platform, SPIN_CYCLES);
+        assert_eq!(part_two_answer, 99291);
+    }
+
+    #[test]
+    fn test_bench() {
+        let platform = test_data();
+        let bit_platform = BitPlatform::try_from(&platform).unwrap();
+
+        assert_eq!(bench(&platform).0, 64);
+        assert_eq!(bench(&bit_platform).0, 64);
+    }
 }
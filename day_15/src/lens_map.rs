@@ -0,0 +1,258 @@
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+
+const BUCKET_COUNT: usize = 256;
+
+// The puzzle's HASH algorithm. It only ever produces 0..=255, which is what
+// makes it usable as a bucket index without any further reduction
+#[derive(Default, Clone)]
+pub struct HolidayHasher {
+    state: u8,
+}
+
+impl Hasher for HolidayHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state = self.state.wrapping_add(*byte).wrapping_mul(17);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.state as u64
+    }
+}
+
+pub type BuildHolidayHasher = BuildHasherDefault<HolidayHasher>;
+
+// `str` adds a terminator byte when it's hashed, which would put every label in
+// the wrong box, so labels hash as their raw bytes instead
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Label<'a>(pub &'a str);
+
+impl Hash for Label<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(self.0.as_bytes());
+    }
+}
+
+// An open hashing map with a fixed 256 buckets, where each bucket keeps its
+// entries in the order they were first inserted
+#[derive(Debug, Clone)]
+pub struct LensMap<K, V, S = BuildHolidayHasher> {
+    buckets: Vec<Vec<(K, V)>>,
+    build_hasher: S,
+}
+
+impl<K: Hash + Eq, V> LensMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(BuildHolidayHasher::default())
+    }
+}
+
+impl<K: Hash + Eq, V> Default for LensMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> LensMap<K, V, S> {
+    pub fn with_hasher(build_hasher: S) -> Self {
+        Self {
+            buckets: (0..BUCKET_COUNT).map(|_| vec![]).collect(),
+            build_hasher,
+        }
+    }
+
+    pub fn bucket_index(&self, key: &K) -> usize {
+        self.build_hasher.hash_one(key) as usize % BUCKET_COUNT
+    }
+
+    // Replacing a value keeps the entry where it is, only new keys go to the back
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let bucket_index = self.bucket_index(&key);
+        let bucket = &mut self.buckets[bucket_index];
+
+        match bucket.iter_mut().find(|(existing, _)| existing == &key) {
+            Some((_, existing_value)) => Some(std::mem::replace(existing_value, value)),
+            None => {
+                bucket.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let bucket_index = self.bucket_index(key);
+        let bucket = &mut self.buckets[bucket_index];
+        let position = bucket.iter().position(|(existing, _)| existing == key)?;
+
+        Some(bucket.remove(position).1)
+    }
+
+    // the puzzle never reads the map back by key, but a map without get isn't much use
+    #[allow(dead_code)]
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.buckets[self.bucket_index(key)]
+            .iter()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value)
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(|bucket| bucket.len()).sum()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter().all(|bucket| bucket.is_empty())
+    }
+
+    pub fn buckets(&self) -> impl Iterator<Item = &[(K, V)]> {
+        self.buckets.iter().map(|bucket| bucket.as_slice())
+    }
+
+    #[allow(dead_code)]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.buckets
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key, value))
+    }
+}
+
+#[cfg(test)]
+mod test_super {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_holiday_hasher() {
+        let build_hasher = BuildHolidayHasher::default();
+
+        assert_eq!(build_hasher.hash_one(Label("HASH")), 52);
+        assert_eq!(build_hasher.hash_one(Label("rn")), 0);
+        assert_eq!(build_hasher.hash_one(Label("qp")), 1);
+        assert_eq!(build_hasher.hash_one(Label("")), 0);
+    }
+
+    #[test]
+    fn test_insert_replace_remove() {
+        let mut lens_map: LensMap<Label, u32> = LensMap::new();
+
+        assert_eq!(lens_map.insert(Label("rn"), 1), None);
+        assert_eq!(lens_map.insert(Label("cm"), 2), None);
+        assert_eq!(lens_map.insert(Label("qp"), 3), None);
+        assert_eq!(lens_map.insert(Label("rn"), 4), Some(1));
+
+        assert_eq!(lens_map.get(&Label("rn")), Some(&4));
+        assert_eq!(lens_map.len(), 3);
+
+        // rn and cm both land in bucket 0, and rn keeps its place after being replaced
+        let entries: Vec<(&Label, &u32)> = lens_map.iter().collect();
+        assert_eq!(
+            entries,
+            vec![(&Label("rn"), &4), (&Label("cm"), &2), (&Label("qp"), &3)]
+        );
+
+        assert_eq!(lens_map.remove(&Label("rn")), Some(4));
+        assert_eq!(lens_map.remove(&Label("rn")), None);
+        assert_eq!(lens_map.get(&Label("rn")), None);
+
+        lens_map.remove(&Label("cm"));
+        lens_map.remove(&Label("qp"));
+        assert!(lens_map.is_empty());
+    }
+
+    // The puzzle's HASH algorithm worked out character by character, so the model
+    // doesn't rely on the map (or its hasher) to decide where each label goes
+    fn reference_box(label: &str) -> usize {
+        label
+            .chars()
+            .fold(0, |value, c| (value + c as usize) * 17 % BUCKET_COUNT)
+    }
+
+    #[test]
+    fn test_reference_box() {
+        assert_eq!(reference_box("HASH"), 52);
+        assert_eq!(reference_box("rn"), 0);
+        assert_eq!(reference_box("qp"), 1);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Action {
+        Insert(String, u32),
+        Remove(String),
+    }
+
+    fn action() -> impl Strategy<Value = Action> {
+        // a small label alphabet so inserts and removes keep hitting the same keys
+        let label = "[a-d]{1,3}";
+
+        prop_oneof![
+            (label, 1..10_u32).prop_map(|(label, value)| Action::Insert(label, value)),
+            label.prop_map(Action::Remove),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_matches_reference_model(actions in prop::collection::vec(action(), 0..200)) {
+            let mut lens_map: LensMap<Label, u32> = LensMap::new();
+            let mut model: Vec<Vec<(Label, u32)>> = vec![vec![]; BUCKET_COUNT];
+
+            for action in &actions {
+                match action {
+                    Action::Insert(label, value) => {
+                        let label = Label(label);
+                        let bucket = &mut model[reference_box(label.0)];
+                        let expected = match bucket.iter_mut().find(|(key, _)| key == &label) {
+                            Some(entry) => Some(std::mem::replace(&mut entry.1, *value)),
+                            None => {
+                                bucket.push((label, *value));
+                                None
+                            }
+                        };
+
+                        prop_assert_eq!(lens_map.insert(label, *value), expected);
+                    }
+                    Action::Remove(label) => {
+                        let label = Label(label);
+                        let bucket = &mut model[reference_box(label.0)];
+                        let expected = bucket
+                            .iter()
+                            .position(|(key, _)| key == &label)
+                            .map(|position| bucket.remove(position).1);
+
+                        prop_assert_eq!(lens_map.remove(&label), expected);
+                    }
+                }
+
+                let buckets: Vec<Vec<(Label, u32)>> =
+                    lens_map.buckets().map(|bucket| bucket.to_vec()).collect();
+                prop_assert_eq!(&buckets, &model);
+                prop_assert_eq!(lens_map.len(), model.iter().map(|bucket| bucket.len()).sum::<usize>());
+            }
+        }
+
+        #[test]
+        fn test_get_matches_reference_model(entries in prop::collection::vec(("[a-d]{1,3}", 1..10_u32), 0..50)) {
+            let mut lens_map: LensMap<Label, u32> = LensMap::new();
+            let mut model: Vec<(&str, u32)> = vec![];
+
+            for (label, value) in &entries {
+                lens_map.insert(Label(label), *value);
+
+                match model.iter_mut().find(|(key, _)| key == label) {
+                    Some(entry) => entry.1 = *value,
+                    None => model.push((label, *value)),
+                }
+            }
+
+            for (label, value) in &model {
+                prop_assert_eq!(lens_map.get(&Label(label)), Some(value));
+                prop_assert_eq!(lens_map.bucket_index(&Label(label)), reference_box(label));
+            }
+        }
+    }
+}
@@ -1,9 +1,20 @@
-fn main() {
+mod lens_map;
+
+use std::{error::Error, hash::Hasher};
+
+use lens_map::{HolidayHasher, Label, LensMap};
+
+fn main() -> Result<(), Box<dyn Error>> {
     let input = include_str!("../data/puzzle_input.txt");
     let init_sequence = process_input(input);
 
     let part_one_answer = part_one_solution(&init_sequence);
     println!("Part one answer is {part_one_answer}");
+
+    let part_two_answer = part_two_solution(&init_sequence)?;
+    println!("Part two answer is {part_two_answer}");
+
+    Ok(())
 }
 
 fn part_one_solution(sequence: &[&str]) -> u32 {
@@ -12,19 +23,83 @@ fn part_one_solution(sequence: &[&str]) -> u32 {
         .fold(0, |acc, step| acc + calculate_hash(step))
 }
 
+fn part_two_solution(sequence: &[&str]) -> Result<u32, String> {
+    let mut boxes: LensMap<Label, u32> = LensMap::new();
+
+    for step in sequence {
+        let step = parse_step(step)?;
+
+        match step.operation {
+            Operation::Insert(focal_length) => {
+                boxes.insert(Label(step.label), focal_length);
+            }
+            Operation::Remove => {
+                boxes.remove(&Label(step.label));
+            }
+        }
+    }
+
+    Ok(focusing_power(&boxes))
+}
+
+fn focusing_power(boxes: &LensMap<Label, u32>) -> u32 {
+    boxes
+        .buckets()
+        .enumerate()
+        .flat_map(|(box_idx, lenses)| {
+            lenses
+                .iter()
+                .enumerate()
+                .map(move |(slot_idx, (_, focal_length))| {
+                    (box_idx as u32 + 1) * (slot_idx as u32 + 1) * focal_length
+                })
+        })
+        .sum()
+}
+
+#[derive(Debug, PartialEq)]
+enum Operation {
+    Insert(u32),
+    Remove,
+}
+
+#[derive(Debug, PartialEq)]
+struct Step<'a> {
+    label: &'a str,
+    operation: Operation,
+}
+
+fn parse_step(step: &str) -> Result<Step<'_>, String> {
+    if let Some((label, focal_length)) = step.split_once('=') {
+        let focal_length = focal_length
+            .parse::<u32>()
+            .map_err(|_| format!("Could not parse focal length for step: {step}"))?;
+
+        Ok(Step {
+            label,
+            operation: Operation::Insert(focal_length),
+        })
+    } else if let Some(label) = step.strip_suffix('-') {
+        Ok(Step {
+            label,
+            operation: Operation::Remove,
+        })
+    } else {
+        Err(format!(
+            "Could not determine the operation for step: {step}"
+        ))
+    }
+}
+
 fn calculate_hash(step: &str) -> u32 {
-    step.chars().fold(0, |mut acc, char| {
-        let ascii_code = char as u32;
-        acc += ascii_code;
-        acc *= 17;
-        acc %= 256;
+    let mut hasher = HolidayHasher::default();
+    hasher.write(step.as_bytes());
 
-        acc
-    })
+    hasher.finish() as u32
 }
 
 fn process_input(input: &str) -> Vec<&str> {
-    input.split(',').collect()
+    input.trim().split(',').collect()
 }
 
 #[cfg(test)]
@@ -39,6 +114,33 @@ mod test_super {
         assert_eq!(calculate_hash("ot=7"), 231);
     }
 
+    #[test]
+    fn test_process_input() {
+        let sequence = process_input("rn=1,cm-,qp=3\n");
+
+        assert_eq!(sequence, vec!["rn=1", "cm-", "qp=3"]);
+    }
+
+    #[test]
+    fn test_parse_step() {
+        assert_eq!(
+            parse_step("rn=1"),
+            Ok(Step {
+                label: "rn",
+                operation: Operation::Insert(1)
+            })
+        );
+        assert_eq!(
+            parse_step("cm-"),
+            Ok(Step {
+                label: "cm",
+                operation: Operation::Remove
+            })
+        );
+        assert!(parse_step("rn=x").is_err());
+        assert!(parse_step("rn").is_err());
+    }
+
     #[test]
     fn test_part_one_example() {
         let test_data = process_input("rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7");
@@ -56,4 +158,22 @@ mod test_super {
 
         assert_eq!(part_one_solution, 515_974);
     }
+
+    #[test]
+    fn test_part_two_example() {
+        let test_data = process_input("rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7");
+
+        let part_two_example = part_two_solution(&test_data);
+
+        assert_eq!(part_two_example, Ok(145));
+    }
+
+    #[test]
+    fn test_part_two_solution() {
+        let data = process_input(include_str!("../data/puzzle_input.txt"));
+
+        let part_two_solution = part_two_solution(&data);
+
+        assert_eq!(part_two_solution, Ok(265_894));
+    }
 }
@@ -1,87 +1,143 @@
-use std::{collections::HashSet, str::FromStr};
+mod render;
 
+use std::{collections::HashSet, error::Error, path::Path, str::FromStr};
+
+use rayon::prelude::*;
 use strum::EnumString;
 
-fn main() {
+fn main() -> Result<(), Box<dyn Error>> {
     let input = include_str!("../data/puzzle_input.txt");
     let contraption = process_input(input);
 
     let part_one_answer = part_one_solution(&contraption);
     println!("Part One answer is: {part_one_answer}");
+
+    let part_two_answer = part_two_solution(&contraption);
+    println!("Part Two answer is: {part_two_answer}");
+
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.iter().any(|arg| arg == "--animate") {
+        let frames = render::beam_frames(&contraption, (0, 0), Direction::Right);
+        render::play_ansi(&contraption, &frames);
+    }
+
+    if let Some(pos) = args.iter().position(|arg| arg == "--animate-ppm") {
+        let directory = args.get(pos + 1).ok_or("--animate-ppm needs a directory")?;
+        let frames = render::beam_frames(&contraption, (0, 0), Direction::Right);
+
+        render::write_ppm_frames(&contraption, &frames, Path::new(directory))?;
+        println!("Wrote {} frames to {directory}", frames.len());
+    }
+
+    Ok(())
 }
 
 fn part_one_solution(contraption: &[Vec<Tile>]) -> usize {
-    let mut beams: Vec<Beam> = vec![Beam {
-        x: 0,
-        y: 0,
-        direction: Direction::Right,
-    }];
-    let mut energized_tiles: HashSet<&Tile> = HashSet::new();
-    energized_tiles.insert(&contraption[0][0]);
+    energize(contraption, (0, 0), Direction::Right)
+}
+
+fn part_two_solution(contraption: &[Vec<Tile>]) -> usize {
+    // every beam is independent of the others, so they can all run at once
+    edge_entry_points(contraption)
+        .into_par_iter()
+        .map(|(start, direction)| energize(contraption, start, direction))
+        .max()
+        .unwrap_or(0)
+}
 
-    let mut used_splitters: HashSet<&Tile> = HashSet::new();
+// Every tile along the edge paired with the direction pointing into the contraption,
+// so the corners show up twice, once for each edge they're on
+fn edge_entry_points(contraption: &[Vec<Tile>]) -> Vec<((usize, usize), Direction)> {
+    let max_y = contraption.len() - 1;
+    let max_x = contraption[0].len() - 1;
+
+    let from_top = (0..=max_x).map(|x| ((x, 0), Direction::Down));
+    let from_bottom = (0..=max_x).map(|x| ((x, max_y), Direction::Up));
+    let from_left = (0..=max_y).map(|y| ((0, y), Direction::Right));
+    let from_right = (0..=max_y).map(|y| ((max_x, y), Direction::Left));
+
+    from_top
+        .chain(from_bottom)
+        .chain(from_left)
+        .chain(from_right)
+        .collect()
+}
+
+fn energize(contraption: &[Vec<Tile>], start: (usize, usize), direction: Direction) -> usize {
+    let (x, y) = start;
+    let mut beams: Vec<Beam> = vec![Beam { x, y, direction }];
+    let mut visited: HashSet<(usize, usize, Direction)> = HashSet::new();
 
     while !beams.is_empty() {
-        let mut new_beams: Vec<Beam> = vec![];
+        beams = step_beams(contraption, beams, &mut visited);
+    }
 
-        for beam in beams {
-            let current_tile = &contraption[beam.y][beam.x];
+    let energized_tiles: HashSet<(usize, usize)> =
+        visited.into_iter().map(|(x, y, _)| (x, y)).collect();
 
-            if used_splitters.contains(current_tile) {
-                continue;
-            }
+    energized_tiles.len()
+}
 
-            let new_directions = match current_tile.r#type {
-                TileType::Empty => vec![beam.direction],
-                TileType::LeftMirror => match beam.direction {
-                    Direction::Up => vec![Direction::Right],
-                    Direction::Down => vec![Direction::Left],
-                    Direction::Left => vec![Direction::Down],
-                    Direction::Right => vec![Direction::Up],
-                },
-                TileType::RightMirror => match beam.direction {
-                    Direction::Up => vec![Direction::Left],
-                    Direction::Down => vec![Direction::Right],
-                    Direction::Left => vec![Direction::Up],
-                    Direction::Right => vec![Direction::Down],
-                },
-                TileType::VerticalSplitter => match beam.direction {
-                    Direction::Up | Direction::Down => vec![beam.direction],
-                    Direction::Left | Direction::Right => {
-                        used_splitters.insert(current_tile);
-                        vec![Direction::Up, Direction::Down]
-                    }
-                },
-                TileType::HorizontalSplitter => match beam.direction {
-                    Direction::Left | Direction::Right => vec![beam.direction],
-                    Direction::Up | Direction::Down => {
-                        used_splitters.insert(current_tile);
-                        vec![Direction::Left, Direction::Right]
-                    }
-                },
-            };
-
-            for new_direction in new_directions {
-                let next_position = new_position(beam.x, beam.y, &new_direction);
-
-                let next_tile = next_position.and_then(|(x, y)| contraption.get(y)?.get(x));
-
-                if let Some(next_tile) = next_tile {
-                    energized_tiles.insert(next_tile);
-
-                    new_beams.push(Beam {
-                        x: next_tile.x,
-                        y: next_tile.y,
-                        direction: new_direction,
-                    })
-                }
-            }
+// Moves every beam on by one tile. A beam that's somewhere it's already been,
+// heading the same way, can only retrace an earlier path so it gets dropped,
+// which is also what stops beams going round a loop forever
+fn step_beams(
+    contraption: &[Vec<Tile>],
+    beams: Vec<Beam>,
+    visited: &mut HashSet<(usize, usize, Direction)>,
+) -> Vec<Beam> {
+    let mut new_beams: Vec<Beam> = vec![];
+
+    for beam in beams {
+        if !visited.insert((beam.x, beam.y, beam.direction)) {
+            continue;
         }
 
-        beams = new_beams;
+        let current_tile = &contraption[beam.y][beam.x];
+
+        for new_direction in next_directions(&current_tile.r#type, beam.direction) {
+            let next_position = new_position(beam.x, beam.y, &new_direction);
+
+            let next_tile = next_position.and_then(|(x, y)| contraption.get(y)?.get(x));
+
+            if let Some(next_tile) = next_tile {
+                new_beams.push(Beam {
+                    x: next_tile.x,
+                    y: next_tile.y,
+                    direction: new_direction,
+                })
+            }
+        }
     }
 
-    energized_tiles.len()
+    new_beams
+}
+
+fn next_directions(tile_type: &TileType, direction: Direction) -> Vec<Direction> {
+    match tile_type {
+        TileType::Empty => vec![direction],
+        TileType::LeftMirror => match direction {
+            Direction::Up => vec![Direction::Right],
+            Direction::Down => vec![Direction::Left],
+            Direction::Left => vec![Direction::Down],
+            Direction::Right => vec![Direction::Up],
+        },
+        TileType::RightMirror => match direction {
+            Direction::Up => vec![Direction::Left],
+            Direction::Down => vec![Direction::Right],
+            Direction::Left => vec![Direction::Up],
+            Direction::Right => vec![Direction::Down],
+        },
+        TileType::VerticalSplitter => match direction {
+            Direction::Up | Direction::Down => vec![direction],
+            Direction::Left | Direction::Right => vec![Direction::Up, Direction::Down],
+        },
+        TileType::HorizontalSplitter => match direction {
+            Direction::Left | Direction::Right => vec![direction],
+            Direction::Up | Direction::Down => vec![Direction::Left, Direction::Right],
+        },
+    }
 }
 
 fn new_position(
@@ -120,7 +176,7 @@ fn process_input(input: &str) -> Vec<Vec<Tile>> {
         .collect()
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum Direction {
     Up,
     Down,
@@ -128,6 +184,7 @@ enum Direction {
     Right,
 }
 
+#[derive(Clone)]
 struct Beam {
     x: usize,
     y: usize,
@@ -169,4 +226,83 @@ mod test_super {
 
         assert_eq!(part_one_solution(&test_data), 46);
     }
+
+    #[test]
+    fn test_energize() {
+        let test_data = test_data();
+
+        assert_eq!(energize(&test_data, (0, 0), Direction::Right), 46);
+        assert_eq!(energize(&test_data, (3, 0), Direction::Down), 51);
+        assert_eq!(energize(&test_data, (9, 4), Direction::Left), 10);
+    }
+
+    #[test]
+    fn test_edge_entry_points() {
+        let test_data = test_data();
+        let entry_points = edge_entry_points(&test_data);
+
+        assert_eq!(entry_points.len(), 40);
+        assert!(entry_points.contains(&((0, 0), Direction::Down)));
+        assert!(entry_points.contains(&((0, 0), Direction::Right)));
+        assert!(entry_points.contains(&((9, 9), Direction::Up)));
+        assert!(entry_points.contains(&((9, 9), Direction::Left)));
+    }
+
+    #[test]
+    fn test_part_two_example() {
+        let test_data = test_data();
+
+        assert_eq!(part_two_solution(&test_data), 51);
+    }
+
+    #[test]
+    fn test_part_one_answer() {
+        let data = process_input(include_str!("../data/puzzle_input.txt"));
+
+        assert_eq!(part_one_solution(&data), 7939);
+    }
+
+    #[test]
+    fn test_part_two_answer() {
+        let data = process_input(include_str!("../data/puzzle_input.txt"));
+
+        assert_eq!(part_two_solution(&data), 8318);
+    }
+
+    #[test]
+    fn test_energize_mirror_loop() {
+        // starting inside a loop of mirrors, the beam never reaches an edge
+        let contraption = process_input("./.\\\n....\n.\\./");
+
+        assert_eq!(energize(&contraption, (2, 0), Direction::Right), 8);
+    }
+
+    #[test]
+    fn test_energize_splitter_into_mirror_loop() {
+        // the right half of the split goes round the mirrors and comes back into the splitter
+        let contraption = process_input("....\n.-.\\\n....\n.\\./");
+
+        assert_eq!(energize(&contraption, (1, 0), Direction::Down), 10);
+    }
+
+    #[test]
+    fn test_step_beams_prunes_visited_states() {
+        let contraption = process_input("./.\\\n....\n.\\./");
+        let mut visited: HashSet<(usize, usize, Direction)> = HashSet::new();
+        let mut beams = vec![Beam {
+            x: 2,
+            y: 0,
+            direction: Direction::Right,
+        }];
+        let mut generations = 0;
+
+        while !beams.is_empty() {
+            beams = step_beams(&contraption, beams, &mut visited);
+            generations += 1;
+        }
+
+        // 8 tiles around the loop, plus the step that finds the beam back at the start
+        assert_eq!(generations, 9);
+        assert_eq!(visited.len(), 8);
+    }
 }
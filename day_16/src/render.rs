@@ -0,0 +1,303 @@
+use std::{collections::HashSet, fs, io, path::Path, thread, time::Duration};
+
+use crate::{step_beams, Beam, Direction, Tile, TileType};
+
+const FRAME_DELAY: Duration = Duration::from_millis(50);
+// each tile becomes a CELL_SIZE x CELL_SIZE block of pixels in the image frames
+const CELL_SIZE: usize = 5;
+
+const ENERGIZED: &str = "\x1b[33m";
+const BEAM: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+const BACKGROUND_COLOUR: [u8; 3] = [20, 20, 30];
+const ENERGIZED_COLOUR: [u8; 3] = [110, 90, 10];
+const TILE_COLOUR: [u8; 3] = [200, 200, 200];
+const BEAM_COLOUR: [u8; 3] = [255, 60, 60];
+
+pub struct Frame {
+    energized: HashSet<(usize, usize)>,
+    beams: Vec<Beam>,
+}
+
+// One frame per generation of the beam frontier, starting with the beam
+// entering the contraption and ending once every beam has left or looped
+pub fn beam_frames(
+    contraption: &[Vec<Tile>],
+    start: (usize, usize),
+    direction: Direction,
+) -> Vec<Frame> {
+    let (x, y) = start;
+    let mut beams: Vec<Beam> = vec![Beam { x, y, direction }];
+    let mut visited: HashSet<(usize, usize, Direction)> = HashSet::new();
+    let mut frames: Vec<Frame> = vec![];
+
+    while !beams.is_empty() {
+        let mut energized: HashSet<(usize, usize)> =
+            visited.iter().map(|(x, y, _)| (*x, *y)).collect();
+        energized.extend(beams.iter().map(|beam| (beam.x, beam.y)));
+
+        frames.push(Frame {
+            energized,
+            beams: beams.clone(),
+        });
+
+        beams = step_beams(contraption, beams, &mut visited);
+    }
+
+    frames
+}
+
+pub fn play_ansi(contraption: &[Vec<Tile>], frames: &[Frame]) {
+    for frame in frames {
+        println!("{CLEAR_SCREEN}{}", render_ansi(contraption, frame));
+        thread::sleep(FRAME_DELAY);
+    }
+}
+
+pub fn render_ansi(contraption: &[Vec<Tile>], frame: &Frame) -> String {
+    contraption
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|tile| match beam_at(frame, tile) {
+                    Some(direction) => format!("{BEAM}{}{RESET}", arrow(direction)),
+                    None if frame.energized.contains(&(tile.x, tile.y)) => {
+                        format!("{ENERGIZED}{}{RESET}", glyph(&tile.r#type))
+                    }
+                    None => glyph(&tile.r#type).to_string(),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Writes frame_0000.ppm, frame_0001.ppm, ... into the directory, which
+// something like `ffmpeg -i frame_%04d.ppm beams.gif` can stitch together
+pub fn write_ppm_frames(
+    contraption: &[Vec<Tile>],
+    frames: &[Frame],
+    directory: &Path,
+) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+
+    for (idx, frame) in frames.iter().enumerate() {
+        let path = directory.join(format!("frame_{idx:04}.ppm"));
+        fs::write(path, render_ppm(contraption, frame))?;
+    }
+
+    Ok(())
+}
+
+pub fn render_ppm(contraption: &[Vec<Tile>], frame: &Frame) -> Vec<u8> {
+    let width = contraption[0].len() * CELL_SIZE;
+    let height = contraption.len() * CELL_SIZE;
+    let mut pixels: Vec<[u8; 3]> = vec![BACKGROUND_COLOUR; width * height];
+
+    for tile in contraption.iter().flatten() {
+        let background = if frame.energized.contains(&(tile.x, tile.y)) {
+            ENERGIZED_COLOUR
+        } else {
+            BACKGROUND_COLOUR
+        };
+        let (pattern, colour) = match beam_at(frame, tile) {
+            Some(direction) => (arrow_pattern(direction), BEAM_COLOUR),
+            None => (tile_pattern(&tile.r#type), TILE_COLOUR),
+        };
+
+        for (cell_y, pattern_row) in pattern.iter().enumerate() {
+            for (cell_x, filled) in pattern_row.iter().enumerate() {
+                let pixel_x = tile.x * CELL_SIZE + cell_x;
+                let pixel_y = tile.y * CELL_SIZE + cell_y;
+
+                pixels[pixel_y * width + pixel_x] = if *filled { colour } else { background };
+            }
+        }
+    }
+
+    let mut image = format!("P6\n{width} {height}\n255\n").into_bytes();
+    image.extend(pixels.into_iter().flatten());
+
+    image
+}
+
+fn beam_at(frame: &Frame, tile: &Tile) -> Option<Direction> {
+    frame
+        .beams
+        .iter()
+        .find(|beam| beam.x == tile.x && beam.y == tile.y)
+        .map(|beam| beam.direction)
+}
+
+fn arrow(direction: Direction) -> char {
+    match direction {
+        Direction::Up => '↑',
+        Direction::Down => '↓',
+        Direction::Left => '←',
+        Direction::Right => '→',
+    }
+}
+
+fn glyph(tile_type: &TileType) -> char {
+    match tile_type {
+        TileType::Empty => '.',
+        TileType::HorizontalSplitter => '-',
+        TileType::VerticalSplitter => '|',
+        TileType::RightMirror => '\\',
+        TileType::LeftMirror => '/',
+    }
+}
+
+type Pattern = [[bool; CELL_SIZE]; CELL_SIZE];
+
+fn arrow_pattern(direction: Direction) -> Pattern {
+    let right = pattern([
+        "..#..", //
+        "...#.", //
+        "#####", //
+        "...#.", //
+        "..#..",
+    ]);
+
+    // every other arrow is the right facing one turned around
+    match direction {
+        Direction::Right => right,
+        Direction::Left => map_pattern(|x, y| right[y][CELL_SIZE - 1 - x]),
+        Direction::Down => map_pattern(|x, y| right[x][y]),
+        Direction::Up => map_pattern(|x, y| right[x][CELL_SIZE - 1 - y]),
+    }
+}
+
+fn tile_pattern(tile_type: &TileType) -> Pattern {
+    let middle = CELL_SIZE / 2;
+
+    match tile_type {
+        TileType::Empty => map_pattern(|_, _| false),
+        TileType::HorizontalSplitter => map_pattern(|_, y| y == middle),
+        TileType::VerticalSplitter => map_pattern(|x, _| x == middle),
+        TileType::RightMirror => map_pattern(|x, y| x == y),
+        TileType::LeftMirror => map_pattern(|x, y| x + y == CELL_SIZE - 1),
+    }
+}
+
+fn map_pattern(filled: impl Fn(usize, usize) -> bool) -> Pattern {
+    let mut pattern = [[false; CELL_SIZE]; CELL_SIZE];
+
+    for (y, row) in pattern.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            *cell = filled(x, y);
+        }
+    }
+
+    pattern
+}
+
+fn pattern(rows: [&str; CELL_SIZE]) -> Pattern {
+    map_pattern(|x, y| rows[y].as_bytes()[x] == b'#')
+}
+
+#[cfg(test)]
+mod test_super {
+    use super::*;
+    use crate::process_input;
+
+    #[test]
+    fn test_beam_frames() {
+        let contraption = process_input(".|.\n...");
+        let frames = beam_frames(&contraption, (0, 0), Direction::Right);
+
+        // right into the splitter, then down out of it, the up half leaves straight away
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].energized, HashSet::from([(0, 0)]));
+        assert_eq!(frames[2].energized, HashSet::from([(0, 0), (1, 0), (1, 1)]));
+        assert_eq!(frames[2].beams.len(), 1);
+        assert_eq!(frames[2].beams[0].direction, Direction::Down);
+    }
+
+    #[test]
+    fn test_beam_frames_ends_on_loops() {
+        let contraption = process_input("./.\\\n....\n.\\./");
+        let frames = beam_frames(&contraption, (2, 0), Direction::Right);
+
+        assert_eq!(frames.len(), 9);
+        assert_eq!(frames.last().unwrap().energized.len(), 8);
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let contraption = process_input(".|.\n...");
+        let frames = beam_frames(&contraption, (0, 0), Direction::Right);
+
+        assert_eq!(
+            render_ansi(&contraption, &frames[1]),
+            "\x1b[33m.\x1b[0m\x1b[1;31m→\x1b[0m.\n..."
+        );
+        assert_eq!(
+            render_ansi(&contraption, &frames[2]),
+            "\x1b[33m.\x1b[0m\x1b[33m|\x1b[0m.\n.\x1b[1;31m↓\x1b[0m."
+        );
+    }
+
+    #[test]
+    fn test_render_ppm() {
+        let contraption = process_input(".|.\n...");
+        let frames = beam_frames(&contraption, (0, 0), Direction::Right);
+        let image = render_ppm(&contraption, &frames[0]);
+
+        let header = b"P6\n15 10\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 15 * 10 * 3);
+
+        // the centre of the first tile is the middle of the beam's arrow
+        let centre = header.len() + (2 * 15 + 2) * 3;
+        assert_eq!(image[centre..centre + 3], BEAM_COLOUR);
+    }
+
+    #[test]
+    fn test_arrow_pattern() {
+        assert_eq!(
+            arrow_pattern(Direction::Up),
+            pattern([
+                "..#..", //
+                ".###.", //
+                "#.#.#", //
+                "..#..", //
+                "..#..",
+            ])
+        );
+        assert_eq!(
+            arrow_pattern(Direction::Down),
+            pattern([
+                "..#..", //
+                "..#..", //
+                "#.#.#", //
+                ".###.", //
+                "..#..",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_write_ppm_frames() {
+        let contraption = process_input(".|.\n...");
+        let frames = beam_frames(&contraption, (0, 0), Direction::Right);
+        let directory = std::env::temp_dir().join("day_16_test_write_ppm_frames");
+
+        write_ppm_frames(&contraption, &frames, &directory).unwrap();
+
+        let mut written: Vec<String> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        written.sort();
+
+        assert_eq!(
+            written,
+            vec!["frame_0000.ppm", "frame_0001.ppm", "frame_0002.ppm"]
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,16 +1,54 @@
 // Implementation heavily cribbed from https://advent-of-code.xavd.id/writeups/2023/day/18/
-// Still don't fully understand Pick's theorem, I thought I could just pass in a list of vertices but no dice
+use std::error::Error;
 use std::str::FromStr;
 
 use regex::Regex;
 use strum::EnumString;
 
-fn main() {
+use polygon::{Polygon, PolygonError};
+
+mod polygon;
+mod svg;
+
+fn main() -> Result<(), Box<dyn Error>> {
     let input = include_str!("../data/puzzle_input.txt");
-    let instructions = process_input(input);
+    let instructions = process_input(input, Interpretation::Plan);
 
-    let part_one_solution = part_one_answer(&instructions);
+    let part_one_solution = lagoon_size(&instructions)?;
     println!("Part one answer: {part_one_solution}");
+
+    let instructions = process_input(input, Interpretation::ColourCode);
+
+    let part_two_solution = lagoon_size(&instructions)?;
+    println!("Part two answer: {part_two_solution}");
+
+    let args: Vec<String> = std::env::args().collect();
+
+    for (flag, interpretation) in [
+        ("--svg", Interpretation::Plan),
+        ("--svg-part-two", Interpretation::ColourCode),
+    ] {
+        if let Some(pos) = args.iter().position(|arg| arg == flag) {
+            let path = args
+                .get(pos + 1)
+                .ok_or(format!("{flag} needs a file path"))?;
+            let svg = svg::render_svg(&process_input(input, interpretation))?;
+
+            std::fs::write(path, svg)?;
+            println!("Wrote the dig site to {path}");
+        }
+    }
+
+    Ok(())
+}
+
+// Which part of each dig plan line the instructions are read from
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Interpretation {
+    // The direction letter and distance as written (part one)
+    Plan,
+    // The distance and direction hidden in the hex colour code (part two)
+    ColourCode,
 }
 
 #[derive(EnumString, PartialEq, Eq, Debug)]
@@ -27,45 +65,43 @@ enum Direction {
 
 struct Instruction {
     direction: Direction,
-    metres: u32,
-    #[allow(dead_code)]
+    metres: i64,
     colour_code: String,
 }
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 struct Coordinate {
-    x: i32,
-    y: i32,
+    x: i64,
+    y: i64,
 }
 
 impl Coordinate {
-    fn move_to(&self, direction: &Direction, metres: &u32) -> Coordinate {
+    fn move_to(&self, direction: &Direction, metres: &i64) -> Coordinate {
         match direction {
             Direction::Up => Coordinate {
                 x: self.x,
-                y: self.y - (*metres as i32),
+                y: self.y - *metres,
             },
             Direction::Right => Coordinate {
-                x: self.x + (*metres as i32),
+                x: self.x + *metres,
                 y: self.y,
             },
             Direction::Down => Coordinate {
                 x: self.x,
-                y: self.y + (*metres as i32),
+                y: self.y + *metres,
             },
             Direction::Left => Coordinate {
-                x: self.x - (*metres as i32),
+                x: self.x - *metres,
                 y: self.y,
             },
         }
     }
 }
 
-fn part_one_answer(instructions: &[Instruction]) -> f32 {
+fn lagoon_size(instructions: &[Instruction]) -> Result<i64, PolygonError> {
     let coordinates = generate_coordinates(instructions);
-    let border_length = calculate_border(instructions);
-    let shoelace_area = shoelace(&coordinates);
+    let polygon = Polygon::from_closed_path(&coordinates)?;
 
-    picks_theorem(&border_length, &shoelace_area)
+    Ok(polygon.enclosed_points())
 }
 
 fn generate_coordinates(instructions: &[Instruction]) -> Vec<Coordinate> {
@@ -82,38 +118,29 @@ fn generate_coordinates(instructions: &[Instruction]) -> Vec<Coordinate> {
     coordinates
 }
 
-fn shoelace(coordinates: &[Coordinate]) -> f32 {
-    let mut total_x = 0;
-    let mut total_y = 0;
-
-    for i in 0..coordinates.len() {
-        let first = &coordinates[i];
-        let second = coordinates.get(i + 1).unwrap_or(&coordinates[0]);
-
-        total_x += first.x * second.y;
-        total_y += first.y * second.x;
-    }
-
-    total_x.abs_diff(total_y) as f32 / 2.0
-}
-
-fn calculate_border(instructions: &[Instruction]) -> u32 {
-    instructions
-        .iter()
-        .fold(0, |acc, instruction| acc + instruction.metres)
+// Decodes a `#rrggbb` colour code into its direction (the last hex digit) and
+// distance (the first five hex digits)
+fn decode_colour_code(colour_code: &str) -> Result<(Direction, i64), String> {
+    let hex = colour_code
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6)
+        .ok_or_else(|| format!("Malformed colour code: {colour_code}"))?;
+
+    let metres = i64::from_str_radix(&hex[..5], 16)
+        .map_err(|e| format!("Invalid distance in {colour_code}: {e}"))?;
+
+    let direction = match &hex[5..] {
+        "0" => Direction::Right,
+        "1" => Direction::Down,
+        "2" => Direction::Left,
+        "3" => Direction::Up,
+        other => return Err(format!("Invalid direction digit {other} in {colour_code}")),
+    };
+
+    Ok((direction, metres))
 }
 
-fn picks_theorem(border_length: &u32, shoelace_area: &f32) -> f32 {
-    // int(abs(area) - 0.5 * len(outline) + 1) + len(outline)
-
-    let outline_length = *border_length as f32;
-
-    let i = shoelace_area + 1.0 - (outline_length * 0.5);
-
-    i + outline_length
-}
-
-fn process_input(input: &str) -> Vec<Instruction> {
+fn process_input(input: &str, interpretation: Interpretation) -> Vec<Instruction> {
     let re = Regex::new(r"([RDUL]) (\d+)\s\((#[a-z0-9]{6})\)").unwrap();
 
     input
@@ -121,10 +148,18 @@ fn process_input(input: &str) -> Vec<Instruction> {
         .map(|line| {
             let caps = re.captures(line).expect("Regex failed");
 
-            let direction = Direction::from_str(&caps[1]).unwrap();
-            let meters = caps[2].parse::<u32>().unwrap();
             let colour_code = caps[3].to_string();
 
+            let (direction, meters) = match interpretation {
+                Interpretation::Plan => (
+                    Direction::from_str(&caps[1]).unwrap(),
+                    caps[2].parse::<i64>().unwrap(),
+                ),
+                Interpretation::ColourCode => {
+                    decode_colour_code(&colour_code).expect("Colour code decoding failed")
+                }
+            };
+
             Instruction {
                 direction,
                 metres: meters,
@@ -139,7 +174,14 @@ mod test_super {
     use super::*;
 
     fn test_data() -> Vec<Instruction> {
-        process_input(include_str!("../data/test_input.txt"))
+        process_input(include_str!("../data/test_input.txt"), Interpretation::Plan)
+    }
+
+    fn colour_code_test_data() -> Vec<Instruction> {
+        process_input(
+            include_str!("../data/test_input.txt"),
+            Interpretation::ColourCode,
+        )
     }
 
     #[test]
@@ -172,33 +214,75 @@ mod test_super {
     }
 
     #[test]
-    fn test_calculate_border() {
-        assert_eq!(calculate_border(&test_data()), 38);
+    fn test_polygon() {
+        let coordinates = generate_coordinates(&test_data());
+        let polygon = Polygon::from_closed_path(&coordinates).unwrap();
+
+        assert_eq!(polygon.boundary_points(), 38);
+        assert_eq!(polygon.twice_area(), 84);
+        assert_eq!(polygon.interior_points(), 24);
     }
 
     #[test]
-    fn test_shoelace() {
-        let coordinates = generate_coordinates(&test_data());
-        assert_eq!(shoelace(&coordinates), 42.0);
+    fn test_unclosed_dig_plan() {
+        let mut instructions = test_data();
+        instructions.pop();
+
+        assert!(matches!(
+            lagoon_size(&instructions),
+            Err(PolygonError::NotClosed { .. })
+        ));
     }
 
     #[test]
-    fn test_picks_theorem() {
-        let coordinates = generate_coordinates(&test_data());
-        let border_length = calculate_border(&test_data());
-        let shoelace_area = shoelace(&coordinates);
+    fn test_part_one_example() {
+        assert_eq!(lagoon_size(&test_data()), Ok(62))
+    }
 
-        assert_eq!(picks_theorem(&border_length, &shoelace_area), 62.0);
+    #[test]
+    fn test_part_one_solution() {
+        let instructions = &process_input(
+            include_str!("../data/puzzle_input.txt"),
+            Interpretation::Plan,
+        );
+        assert_eq!(lagoon_size(instructions), Ok(46359));
     }
 
     #[test]
-    fn test_part_one_example() {
-        assert_eq!(part_one_answer(&test_data()), 62.0)
+    fn test_decode_colour_code() {
+        assert_eq!(
+            decode_colour_code("#70c710"),
+            Ok((Direction::Right, 461937))
+        );
+        assert_eq!(decode_colour_code("#0dc571"), Ok((Direction::Down, 56407)));
+        assert_eq!(decode_colour_code("#caa173"), Ok((Direction::Up, 829975)));
+        assert!(decode_colour_code("#70c714").is_err());
+        assert!(decode_colour_code("70c710").is_err());
     }
 
     #[test]
-    fn test_part_one_solution() {
-        let instructions = &process_input(include_str!("../data/puzzle_input.txt"));
-        assert_eq!(part_one_answer(instructions), 46359.0);
+    fn test_process_input_colour_code() {
+        let test_data = colour_code_test_data();
+
+        assert_eq!(test_data.len(), 14);
+
+        let first = &test_data[0];
+        assert_eq!(first.direction, Direction::Right);
+        assert_eq!(first.metres, 461937);
+        assert_eq!(first.colour_code, "#70c710");
+    }
+
+    #[test]
+    fn test_part_two_example() {
+        assert_eq!(lagoon_size(&colour_code_test_data()), Ok(952408144115))
+    }
+
+    #[test]
+    fn test_part_two_solution() {
+        let instructions = &process_input(
+            include_str!("../data/puzzle_input.txt"),
+            Interpretation::ColourCode,
+        );
+        assert_eq!(lagoon_size(instructions), Ok(59574883048274));
     }
 }
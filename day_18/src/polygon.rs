@@ -0,0 +1,346 @@
+// Exact measurements of lattice polygons, ie polygons whose vertices all sit on
+// integer coordinates.
+//
+// The shoelace formula gives the area enclosed by the vertices, but that treats
+// the trench as infinitely thin lines through the middle of each cube. Pick's
+// theorem links that area to the number of lattice points on the boundary (b)
+// and strictly inside (i):
+//
+//     A = i + b/2 - 1
+//
+// so once we know A and b we can recover i, and the number of cubes dug out is
+// i + b. Everything is kept as twice the area so it stays an integer.
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::Coordinate;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PolygonError {
+    // The path doesn't finish where it started, so it doesn't enclose anything
+    NotClosed { start: Coordinate, end: Coordinate },
+    // A polygon needs at least three distinct corners to enclose an area
+    TooFewVertices { count: usize },
+}
+
+impl Display for PolygonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PolygonError::NotClosed { start, end } => write!(
+                f,
+                "Path starts at ({}, {}) but ends at ({}, {})",
+                start.x, start.y, end.x, end.y
+            ),
+            PolygonError::TooFewVertices { count } => {
+                write!(f, "A polygon needs at least 3 vertices, found {count}")
+            }
+        }
+    }
+}
+
+impl Error for PolygonError {}
+
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    // each vertex once, with the closing edge from the last back to the first implied
+    vertices: Vec<Coordinate>,
+}
+
+impl Polygon {
+    // Builds a polygon from a path that returns to its starting point, as
+    // produced by walking the dig plan
+    pub fn from_closed_path(path: &[Coordinate]) -> Result<Polygon, PolygonError> {
+        let (start, end) = match (path.first(), path.last()) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Err(PolygonError::TooFewVertices { count: 0 }),
+        };
+
+        if start != end {
+            return Err(PolygonError::NotClosed {
+                start: start.clone(),
+                end: end.clone(),
+            });
+        }
+
+        let vertices = path[..path.len() - 1].to_vec();
+
+        if vertices.len() < 3 {
+            return Err(PolygonError::TooFewVertices {
+                count: vertices.len(),
+            });
+        }
+
+        Ok(Polygon { vertices })
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&Coordinate, &Coordinate)> {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    // Twice the enclosed area, via the shoelace formula. Doubling it means a
+    // lattice polygon's area is always a whole number
+    pub fn twice_area(&self) -> i64 {
+        self.edges()
+            .map(|(first, second)| first.x * second.y - first.y * second.x)
+            .sum::<i64>()
+            .abs()
+    }
+
+    // The number of lattice points on the edges. An edge from (0, 0) to
+    // (dx, dy) passes through gcd(dx, dy) of them, not counting its start
+    pub fn boundary_points(&self) -> i64 {
+        self.edges()
+            .map(|(first, second)| gcd(second.x - first.x, second.y - first.y))
+            .sum()
+    }
+
+    // The number of lattice points strictly inside, from Pick's theorem:
+    // 2A = 2i + b - 2
+    pub fn interior_points(&self) -> i64 {
+        (self.twice_area() - self.boundary_points() + 2) / 2
+    }
+
+    // Every lattice point inside or on the boundary, which for the dig site is
+    // the number of cubes in the lagoon
+    pub fn enclosed_points(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+#[cfg(test)]
+mod test_super {
+    use std::collections::{HashSet, VecDeque};
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn path(points: &[(i64, i64)]) -> Vec<Coordinate> {
+        points.iter().map(|&(x, y)| Coordinate { x, y }).collect()
+    }
+
+    fn rectangle(width: i64, height: i64) -> Vec<Coordinate> {
+        path(&[(0, 0), (width, 0), (width, height), (0, height), (0, 0)])
+    }
+
+    // A width x height rectangle with a cut_width x cut_height notch taken out
+    // of its top right corner
+    fn l_shape(width: i64, height: i64, cut_width: i64, cut_height: i64) -> Vec<Coordinate> {
+        path(&[
+            (0, 0),
+            (width, 0),
+            (width, height - cut_height),
+            (width - cut_width, height - cut_height),
+            (width - cut_width, height),
+            (0, height),
+            (0, 0),
+        ])
+    }
+
+    // A run of vertical strips side by side, each overlapping its neighbours,
+    // traced along the top from left to right then back along the bottom
+    fn skyline(columns: &[(i64, i64, i64)]) -> Vec<Coordinate> {
+        let mut strips: Vec<(i64, i64, i64)> = vec![];
+
+        // widen each strip so it overlaps the previous one, keeping the outline simple
+        for &(width, low, high) in columns {
+            let (low, high) = match strips.last() {
+                Some(&(_, previous_low, previous_high)) => {
+                    (low.min(previous_high - 1), high.max(previous_low + 1))
+                }
+                None => (low, high),
+            };
+            strips.push((width, low, high));
+        }
+
+        let mut points = vec![];
+        let mut x = 0;
+
+        for &(width, _, high) in &strips {
+            points.push((x, high));
+            x += width;
+            points.push((x, high));
+        }
+
+        for &(width, low, _) in strips.iter().rev() {
+            points.push((x, low));
+            x -= width;
+            points.push((x, low));
+        }
+
+        points.push(points[0]);
+        path(&points)
+    }
+
+    // Counts boundary and interior lattice points by drawing the outline onto a
+    // grid and flood filling the outside
+    fn flood_fill_counts(path: &[Coordinate]) -> (i64, i64) {
+        let mut boundary = HashSet::new();
+
+        for edge in path.windows(2) {
+            let (dx, dy) = (
+                (edge[1].x - edge[0].x).signum(),
+                (edge[1].y - edge[0].y).signum(),
+            );
+            let (mut x, mut y) = (edge[0].x, edge[0].y);
+
+            boundary.insert((x, y));
+            while (x, y) != (edge[1].x, edge[1].y) {
+                x += dx;
+                y += dy;
+                boundary.insert((x, y));
+            }
+        }
+
+        let min_x = boundary.iter().map(|p| p.0).min().unwrap() - 1;
+        let max_x = boundary.iter().map(|p| p.0).max().unwrap() + 1;
+        let min_y = boundary.iter().map(|p| p.1).min().unwrap() - 1;
+        let max_y = boundary.iter().map(|p| p.1).max().unwrap() + 1;
+
+        let mut outside = HashSet::from([(min_x, min_y)]);
+        let mut queue = VecDeque::from([(min_x, min_y)]);
+
+        while let Some((x, y)) = queue.pop_front() {
+            for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                let in_bounds =
+                    (min_x..=max_x).contains(&next.0) && (min_y..=max_y).contains(&next.1);
+
+                if in_bounds && !boundary.contains(&next) && outside.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let total = (max_x - min_x + 1) * (max_y - min_y + 1);
+        let boundary_count = boundary.len() as i64;
+
+        (
+            boundary_count,
+            total - outside.len() as i64 - boundary_count,
+        )
+    }
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(gcd(0, -7), 7);
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-4, 6), 2);
+    }
+
+    #[test]
+    fn test_square() {
+        let polygon = Polygon::from_closed_path(&rectangle(2, 2)).unwrap();
+
+        assert_eq!(polygon.twice_area(), 8);
+        assert_eq!(polygon.boundary_points(), 8);
+        assert_eq!(polygon.interior_points(), 1);
+        assert_eq!(polygon.enclosed_points(), 9);
+    }
+
+    #[test]
+    fn test_triangle() {
+        // a diagonal edge from (4, 0) to (0, 4) passes through 3 lattice points on the way
+        let polygon = Polygon::from_closed_path(&path(&[(0, 0), (4, 0), (0, 4), (0, 0)])).unwrap();
+
+        assert_eq!(polygon.twice_area(), 16);
+        assert_eq!(polygon.boundary_points(), 12);
+        assert_eq!(polygon.interior_points(), 3);
+    }
+
+    #[test]
+    fn test_not_closed() {
+        let error = Polygon::from_closed_path(&path(&[(0, 0), (3, 0), (3, 3)])).unwrap_err();
+
+        assert_eq!(
+            error,
+            PolygonError::NotClosed {
+                start: Coordinate { x: 0, y: 0 },
+                end: Coordinate { x: 3, y: 3 }
+            }
+        );
+    }
+
+    #[test]
+    fn test_too_few_vertices() {
+        assert_eq!(
+            Polygon::from_closed_path(&[]).unwrap_err(),
+            PolygonError::TooFewVertices { count: 0 }
+        );
+        assert_eq!(
+            Polygon::from_closed_path(&path(&[(0, 0), (3, 0), (0, 0)])).unwrap_err(),
+            PolygonError::TooFewVertices { count: 2 }
+        );
+    }
+
+    #[test]
+    fn test_flood_fill_reference() {
+        assert_eq!(flood_fill_counts(&rectangle(2, 2)), (8, 1));
+        assert_eq!(flood_fill_counts(&l_shape(4, 4, 2, 2)), (16, 5));
+    }
+
+    proptest! {
+        #[test]
+        fn test_rectangles(width in 1..1_000_000_i64, height in 1..1_000_000_i64) {
+            let polygon = Polygon::from_closed_path(&rectangle(width, height)).unwrap();
+
+            prop_assert_eq!(polygon.twice_area(), 2 * width * height);
+            prop_assert_eq!(polygon.boundary_points(), 2 * (width + height));
+            prop_assert_eq!(polygon.interior_points(), (width - 1) * (height - 1));
+            prop_assert_eq!(polygon.enclosed_points(), (width + 1) * (height + 1));
+        }
+
+        #[test]
+        fn test_l_shapes(
+            (width, height, cut_width, cut_height) in (2..20_i64, 2..20_i64)
+                .prop_flat_map(|(width, height)| (Just(width), Just(height), 1..width, 1..height))
+        ) {
+            let path = l_shape(width, height, cut_width, cut_height);
+            let polygon = Polygon::from_closed_path(&path).unwrap();
+            let (boundary, interior) = flood_fill_counts(&path);
+
+            prop_assert_eq!(
+                polygon.twice_area(),
+                2 * (width * height - cut_width * cut_height)
+            );
+            prop_assert_eq!(polygon.boundary_points(), boundary);
+            prop_assert_eq!(polygon.interior_points(), interior);
+        }
+
+        #[test]
+        fn test_random_rectilinear(
+            columns in prop::collection::vec((1..4_i64, -10..10_i64, 1..10_i64), 1..10),
+            reversed in any::<bool>(),
+        ) {
+            let columns: Vec<(i64, i64, i64)> = columns
+                .into_iter()
+                .map(|(width, low, height)| (width, low, low + height))
+                .collect();
+
+            let mut path = skyline(&columns);
+            // the winding direction shouldn't matter
+            if reversed {
+                path.reverse();
+            }
+
+            let polygon = Polygon::from_closed_path(&path).unwrap();
+            let (boundary, interior) = flood_fill_counts(&path);
+
+            prop_assert_eq!(polygon.boundary_points(), boundary);
+            prop_assert_eq!(polygon.interior_points(), interior);
+            prop_assert_eq!(polygon.enclosed_points(), boundary + interior);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// Draws the dig plan as an SVG, with the lagoon filled in and each length of
+// trench stroked in the colour it was painted.
+//
+// Part two's trench is millions of metres across, so rather than leaving the
+// raw coordinates in the file everything is scaled to fit a fixed size canvas.
+use std::fmt::Write;
+
+use crate::{generate_coordinates, Coordinate, Instruction, Polygon, PolygonError};
+
+// length of the longest side of the drawing, not counting the margin
+const CANVAS_SIZE: f64 = 1000.0;
+const MARGIN: f64 = 10.0;
+const STROKE_WIDTH: f64 = 2.0;
+const LAGOON_FILL: &str = "#a9cfe0";
+
+struct Transform {
+    min_x: i64,
+    min_y: i64,
+    scale: f64,
+}
+
+impl Transform {
+    fn fit(coordinates: &[Coordinate]) -> Transform {
+        let min_x = coordinates.iter().map(|c| c.x).min().unwrap_or(0);
+        let max_x = coordinates.iter().map(|c| c.x).max().unwrap_or(0);
+        let min_y = coordinates.iter().map(|c| c.y).min().unwrap_or(0);
+        let max_y = coordinates.iter().map(|c| c.y).max().unwrap_or(0);
+
+        let extent = (max_x - min_x).max(max_y - min_y).max(1);
+
+        Transform {
+            min_x,
+            min_y,
+            scale: CANVAS_SIZE / extent as f64,
+        }
+    }
+
+    fn apply(&self, coordinate: &Coordinate) -> (f64, f64) {
+        (
+            (coordinate.x - self.min_x) as f64 * self.scale + MARGIN,
+            (coordinate.y - self.min_y) as f64 * self.scale + MARGIN,
+        )
+    }
+}
+
+pub fn render_svg(instructions: &[Instruction]) -> Result<String, PolygonError> {
+    let coordinates = generate_coordinates(instructions);
+    // only closed trenches hold a lagoon worth drawing
+    Polygon::from_closed_path(&coordinates)?;
+
+    let transform = Transform::fit(&coordinates);
+    let points: Vec<(f64, f64)> = coordinates.iter().map(|c| transform.apply(c)).collect();
+
+    let (width, height) = points.iter().fold((0.0_f64, 0.0_f64), |acc, point| {
+        (acc.0.max(point.0 + MARGIN), acc.1.max(point.1 + MARGIN))
+    });
+
+    let mut svg = String::new();
+
+    // writing to a String can't fail
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {width:.1} {height:.1}" width="{width:.1}" height="{height:.1}">"#
+    )
+    .unwrap();
+
+    let polygon_points: Vec<String> = points
+        .iter()
+        .map(|(x, y)| format!("{x:.1},{y:.1}"))
+        .collect();
+    writeln!(
+        svg,
+        r#"  <polygon points="{}" fill="{LAGOON_FILL}" stroke="none"/>"#,
+        polygon_points.join(" ")
+    )
+    .unwrap();
+
+    writeln!(
+        svg,
+        r#"  <g stroke-width="{STROKE_WIDTH}" stroke-linecap="square">"#
+    )
+    .unwrap();
+    for (instruction, edge) in instructions.iter().zip(points.windows(2)) {
+        let ((x1, y1), (x2, y2)) = (edge[0], edge[1]);
+
+        writeln!(
+            svg,
+            r#"    <line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke="{}"/>"#,
+            instruction.colour_code
+        )
+        .unwrap();
+    }
+    writeln!(svg, "  </g>").unwrap();
+    writeln!(svg, "</svg>").unwrap();
+
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod test_super {
+    use super::*;
+    use crate::{process_input, Interpretation};
+
+    fn test_data(interpretation: Interpretation) -> Vec<Instruction> {
+        process_input(include_str!("../data/test_input.txt"), interpretation)
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let svg = render_svg(&test_data(Interpretation::Plan)).unwrap();
+
+        // the example is 6 wide and 9 tall, so the height fills the canvas
+        assert!(svg
+            .starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 686.7 1020.0""#));
+        assert!(svg.ends_with("</svg>\n"));
+
+        assert_eq!(svg.matches("<polygon").count(), 1);
+        assert_eq!(svg.matches("<line").count(), 14);
+        assert!(
+            svg.contains(r##"<line x1="10.0" y1="10.0" x2="676.7" y2="10.0" stroke="#70c710"/>"##)
+        );
+        assert!(svg.contains(r##"stroke="#caa173""##));
+    }
+
+    #[test]
+    fn test_render_svg_scales_part_two() {
+        let instructions = process_input(
+            include_str!("../data/puzzle_input.txt"),
+            Interpretation::ColourCode,
+        );
+        let svg = render_svg(&instructions).unwrap();
+
+        let coordinates = svg
+            .split('"')
+            .filter_map(|value| value.parse::<f64>().ok())
+            .collect::<Vec<f64>>();
+
+        assert!(coordinates
+            .iter()
+            .all(|&value| (0.0..=CANVAS_SIZE + 2.0 * MARGIN).contains(&value)));
+    }
+
+    #[test]
+    fn test_render_svg_unclosed() {
+        let mut instructions = test_data(Interpretation::Plan);
+        instructions.pop();
+
+        assert!(matches!(
+            render_svg(&instructions),
+            Err(PolygonError::NotClosed { .. })
+        ));
+    }
+}
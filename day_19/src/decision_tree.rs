@@ -0,0 +1,384 @@
+// The workflows compiled down to something that can classify a part without
+// any string lookups.
+//
+// Every workflow gets an index and every destination becomes either a final
+// verdict or the index of the next workflow. Since the workflows never change
+// they're also simplified up front: rules that can never match are dropped,
+// and workflows that always give the same verdict are folded into whatever
+// points at them.
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{index_workflows, OutCome, Part, PartRange, Queue, Rule};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Accepted,
+    Rejected,
+    Workflow(usize),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CompileError {
+    MissingStart { name: String },
+    UndefinedTarget { workflow: String, target: String },
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::MissingStart { name } => {
+                write!(f, "Could not find the \"{name}\" workflow to start from")
+            }
+            CompileError::UndefinedTarget { workflow, target } => {
+                write!(
+                    f,
+                    "Workflow {workflow} sends parts to undefined workflow {target}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for CompileError {}
+
+#[derive(Debug)]
+struct Workflow {
+    rules: Vec<Rule<Target>>,
+    default: Target,
+}
+
+impl Workflow {
+    fn next(&self, part: &Part) -> Target {
+        self.rules
+            .iter()
+            .find_map(|rule| rule.check(part))
+            .unwrap_or(self.default)
+    }
+
+    // Drops rules that can't match anything the earlier rules haven't already
+    // caught, and stops at the first rule that catches everything left over.
+    // Returns whether anything changed
+    fn prune_rules(&mut self) -> bool {
+        let rule_count = self.rules.len();
+        let default = self.default;
+
+        let mut remaining = PartRange::all();
+        let mut kept = vec![];
+
+        for rule in self.rules.drain(..) {
+            let (matching, not_matching) = rule.split(remaining.clone());
+
+            if matching.is_none() {
+                continue;
+            }
+
+            match not_matching {
+                Some(rest) => {
+                    remaining = rest;
+                    kept.push(rule);
+                }
+                None => {
+                    self.default = rule.outcome;
+                    break;
+                }
+            }
+        }
+
+        // a rule at the end that goes to the same place as the default makes no difference
+        while kept.last().is_some_and(|rule| rule.outcome == self.default) {
+            kept.pop();
+        }
+
+        self.rules = kept;
+        self.rules.len() != rule_count || self.default != default
+    }
+}
+
+#[derive(Debug)]
+pub struct DecisionTree {
+    workflows: Vec<Workflow>,
+    start: Target,
+}
+
+impl DecisionTree {
+    pub fn compile(queues: &HashMap<String, Queue>, start: &str) -> Result<Self, CompileError> {
+        let (names, indices) = index_workflows(queues);
+
+        let resolve = |workflow: &str, outcome: &OutCome| match outcome {
+            OutCome::Accepted => Ok(Target::Accepted),
+            OutCome::Rejected => Ok(Target::Rejected),
+            OutCome::Redirect(target) => indices
+                .get(target.as_str())
+                .map(|&index| Target::Workflow(index))
+                .ok_or_else(|| CompileError::UndefinedTarget {
+                    workflow: workflow.to_string(),
+                    target: target.clone(),
+                }),
+        };
+
+        let workflows = names
+            .iter()
+            .map(|name| {
+                let queue = &queues[*name];
+
+                let rules = queue
+                    .rules
+                    .iter()
+                    .map(|rule| {
+                        Ok(Rule {
+                            category: rule.category,
+                            comparison_type: rule.comparison_type,
+                            amount: rule.amount,
+                            outcome: resolve(name, &rule.outcome)?,
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Workflow {
+                    rules,
+                    default: resolve(name, &queue.default)?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let start = indices
+            .get(start)
+            .map(|&index| Target::Workflow(index))
+            .ok_or_else(|| CompileError::MissingStart {
+                name: start.to_string(),
+            })?;
+
+        let mut tree = DecisionTree { workflows, start };
+        tree.simplify();
+
+        Ok(tree)
+    }
+
+    // Pruning can leave a workflow with nothing but a default, and folding that
+    // in elsewhere can make more rules redundant, so keep going until neither
+    // finds anything left to do
+    fn simplify(&mut self) {
+        loop {
+            let mut changed = false;
+
+            for workflow in &mut self.workflows {
+                changed |= workflow.prune_rules();
+            }
+            changed |= self.fold_constants();
+
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    // Points everything that leads to a workflow without any rules straight at
+    // wherever that workflow sends everything instead
+    fn fold_constants(&mut self) -> bool {
+        let resolved: Vec<Target> = (0..self.workflows.len())
+            .map(|index| self.resolve_constant(Target::Workflow(index)))
+            .collect();
+
+        let fold = |target: &mut Target| match *target {
+            Target::Workflow(index) if resolved[index] != *target => {
+                *target = resolved[index];
+                true
+            }
+            _ => false,
+        };
+
+        let mut changed = fold(&mut self.start);
+
+        for workflow in &mut self.workflows {
+            for rule in &mut workflow.rules {
+                changed |= fold(&mut rule.outcome);
+            }
+            changed |= fold(&mut workflow.default);
+        }
+
+        changed
+    }
+
+    // Follows a chain of rule-less workflows to where it ends up. A chain that
+    // loops back on itself is left alone, there's nothing sensible to fold it into
+    fn resolve_constant(&self, target: Target) -> Target {
+        let mut current = target;
+        let mut steps = 0;
+
+        while let Target::Workflow(index) = current {
+            let workflow = &self.workflows[index];
+
+            if !workflow.rules.is_empty() {
+                break;
+            }
+            if steps == self.workflows.len() {
+                return target;
+            }
+
+            current = workflow.default;
+            steps += 1;
+        }
+
+        current
+    }
+
+    // Runs a part through the workflows. The workflows must not loop, or
+    // this never returns, which `validate` checks for up front
+    pub fn accepts(&self, part: &Part) -> bool {
+        let mut target = self.start;
+
+        loop {
+            match target {
+                Target::Accepted => return true,
+                Target::Rejected => return false,
+                Target::Workflow(index) => target = self.workflows[index].next(part),
+            }
+        }
+    }
+
+    // Counts every part within the range that would be accepted. Each rule
+    // splits off the slice of the range it matches and passes the rest on to
+    // the next rule, so every part follows exactly the path it would on its own
+    pub fn count_accepted(&self, range: PartRange) -> u64 {
+        self.count_target(range, self.start)
+    }
+
+    fn count_target(&self, range: PartRange, target: Target) -> u64 {
+        let workflow = match target {
+            Target::Accepted => return range.volume(),
+            Target::Rejected => return 0,
+            Target::Workflow(index) => &self.workflows[index],
+        };
+
+        let mut remaining = Some(range);
+        let mut accepted = 0;
+
+        for rule in &workflow.rules {
+            let Some(range) = remaining else {
+                break;
+            };
+
+            let (matching, not_matching) = rule.split(range);
+
+            if let Some(matching) = matching {
+                accepted += self.count_target(matching, rule.outcome);
+            }
+
+            remaining = not_matching;
+        }
+
+        if let Some(range) = remaining {
+            accepted += self.count_target(range, workflow.default);
+        }
+
+        accepted
+    }
+}
+
+#[cfg(test)]
+mod test_super {
+    use super::*;
+    use crate::process_input;
+
+    fn compile(workflows: &str) -> Result<DecisionTree, CompileError> {
+        let (queues, _) = process_input(&format!("{workflows}\n\n{{x=1,m=1,a=1,s=1}}"));
+
+        DecisionTree::compile(&queues, "in")
+    }
+
+    #[test]
+    fn test_compile_indices() {
+        let tree = compile("in{x>10:a,m<5:R,b}\na{s>100:R,A}\nb{a<200:A,R}").unwrap();
+
+        // workflows are numbered alphabetically
+        assert_eq!(tree.start, Target::Workflow(2));
+        assert_eq!(tree.workflows[2].rules[0].outcome, Target::Workflow(0));
+        assert_eq!(tree.workflows[2].rules[1].outcome, Target::Rejected);
+        assert_eq!(tree.workflows[2].default, Target::Workflow(1));
+    }
+
+    #[test]
+    fn test_compile_folds_constants() {
+        let tree =
+            compile("in{x>10:a,m<5:b,c}\na{s>100:A,A}\nb{x<5:R,x<3:A,R}\nc{x>0:A,R}").unwrap();
+
+        // a always accepts, b always rejects and c's only rule matches every part
+        assert!(tree.workflows[..3].iter().all(|w| w.rules.is_empty()));
+        assert_eq!(tree.workflows[0].default, Target::Accepted);
+        assert_eq!(tree.workflows[1].default, Target::Rejected);
+        assert_eq!(tree.workflows[2].default, Target::Accepted);
+
+        let start = &tree.workflows[3];
+        assert_eq!(start.rules.len(), 2);
+        assert_eq!(start.rules[0].outcome, Target::Accepted);
+        assert_eq!(start.rules[1].outcome, Target::Rejected);
+        assert_eq!(start.default, Target::Accepted);
+    }
+
+    #[test]
+    fn test_compile_to_constant() {
+        let tree = compile("in{x>10:a,b}\na{m<5:R,R}\nb{s>1:R,R}").unwrap();
+
+        assert_eq!(tree.start, Target::Rejected);
+        assert_eq!(tree.count_accepted(PartRange::all()), 0);
+    }
+
+    #[test]
+    fn test_prune_subsumed_rules() {
+        let mut workflow = compile("in{x<100:R,x<50:A,m>10:A,x>200:A,x>150:R,R}")
+            .unwrap()
+            .workflows
+            .remove(0);
+
+        // x<50 can't match after x<100, and x>150 sends parts where the default would
+        assert_eq!(
+            workflow
+                .rules
+                .iter()
+                .map(|rule| rule.amount)
+                .collect::<Vec<_>>(),
+            vec![100, 10, 200]
+        );
+        assert_eq!(workflow.default, Target::Rejected);
+        assert!(!workflow.prune_rules());
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        assert_eq!(
+            compile("in{x>10:a,R}").unwrap_err(),
+            CompileError::UndefinedTarget {
+                workflow: "in".to_string(),
+                target: "a".to_string()
+            }
+        );
+        assert_eq!(
+            compile("start{x>10:A,R}").unwrap_err(),
+            CompileError::MissingStart {
+                name: "in".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_constant_loop() {
+        // nothing to fold a loop into, but it mustn't hang the compiler either
+        let tree = compile("in{x>10:a,R}\na{b}\nb{a}").unwrap();
+
+        assert_eq!(tree.workflows[0].default, Target::Workflow(1));
+        assert_eq!(tree.workflows[1].default, Target::Workflow(0));
+    }
+
+    #[test]
+    fn test_accepts() {
+        let (queues, parts) = process_input(include_str!("../data/test_input.txt"));
+        let tree = DecisionTree::compile(&queues, "in").unwrap();
+
+        let verdicts: Vec<bool> = parts.iter().map(|part| tree.accepts(part)).collect();
+        assert_eq!(verdicts, vec![true, false, true, false, true]);
+    }
+}
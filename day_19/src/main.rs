@@ -1,57 +1,74 @@
-// TODO: Cache the results for each rule to speed up execution.
-//      Since the data is static once a rule has been resolved to the
-//      eventual outcome then it does not need to be re-calculated.
-use std::{collections::HashMap, str::FromStr};
+mod decision_tree;
+mod validate;
+
+use std::{collections::HashMap, error::Error, ops::Range, str::FromStr};
+
+use decision_tree::{CompileError, DecisionTree};
 
 use regex::Regex;
 use strum::EnumString;
 
 type InputData = (HashMap<String, Queue>, Vec<Part>);
 
-fn main() {
+// Every category of a part is rated from 1 to 4000 inclusive
+const RATINGS: Range<u32> = 1..4001;
+
+fn main() -> Result<(), Box<dyn Error>> {
     let input = include_str!("../data/puzzle_input.txt");
     let data = process_input(input);
 
-    let part_one_answer = part_one_solution(&data);
+    let diagnostics = validate::validate(&data.0, "in");
+    let verbose = std::env::args().any(|arg| arg == "--validate");
+
+    for diagnostic in &diagnostics {
+        if verbose || diagnostic.is_error() {
+            eprintln!("{diagnostic}");
+        }
+    }
+    if diagnostics.iter().any(validate::Diagnostic::is_error) {
+        return Err("The workflows are not valid".into());
+    }
+
+    let part_one_answer = part_one_solution(&data)?;
     println!("Part one answer is: {part_one_answer}");
-}
 
-fn part_one_solution(data: &InputData) -> u64 {
-    let (queues, parts) = &data;
-    let mut rejected_parts: Vec<&Part> = vec![];
-    let mut accepted_parts: Vec<&Part> = vec![];
-    let first_queue = queues.get("in").expect("Could not find \"in\" queue");
+    let part_two_answer = part_two_solution(&data)?;
+    println!("Part two answer is: {part_two_answer}");
 
-    parts.iter().for_each(|part| {
-        let outcome = apply_queue(part, first_queue, queues);
+    Ok(())
+}
 
-        match outcome {
-            OutCome::Accepted => accepted_parts.push(part),
-            OutCome::Rejected => rejected_parts.push(part),
-            _ => panic!("Should not have returned a non final outcome"),
-        }
-    });
+fn part_one_solution(data: &InputData) -> Result<u64, CompileError> {
+    let (queues, parts) = &data;
+    let tree = DecisionTree::compile(queues, "in")?;
 
-    accepted_parts
+    Ok(parts
         .iter()
-        .fold(0, |acc, ap| acc + ap.total_value())
+        .filter(|part| tree.accepts(part))
+        .map(|part| part.total_value())
+        .sum())
 }
 
-fn apply_queue(part: &Part, queue: &Queue, queues: &HashMap<String, Queue>) -> OutCome {
-    let rule_check = queue.rules.iter().find_map(|rule| rule.check(part));
+fn part_two_solution(data: &InputData) -> Result<u64, CompileError> {
+    let (queues, _) = &data;
+    let tree = DecisionTree::compile(queues, "in")?;
 
-    let queue_outcome = rule_check.unwrap_or(queue.default.clone());
+    Ok(tree.count_accepted(PartRange::all()))
+}
 
-    match queue_outcome {
-        OutCome::Redirect(next_queue_name) => {
-            let next_queue = queues
-                .get(&next_queue_name)
-                .expect("could not find next queue");
+// Numbers the workflows in name order, so the same workflows always end up with
+// the same indices
+fn index_workflows(queues: &HashMap<String, Queue>) -> (Vec<&str>, HashMap<&str, usize>) {
+    let mut names: Vec<&str> = queues.keys().map(|name| name.as_str()).collect();
+    names.sort();
 
-            apply_queue(part, next_queue, queues)
-        }
-        final_outcome => final_outcome,
-    }
+    let indices = names
+        .iter()
+        .enumerate()
+        .map(|(index, &name)| (name, index))
+        .collect();
+
+    (names, indices)
 }
 
 fn process_input(input: &str) -> InputData {
@@ -144,7 +161,7 @@ struct Queue {
     default: OutCome,
 }
 
-#[derive(EnumString, PartialEq, Eq, Debug)]
+#[derive(EnumString, PartialEq, Eq, Debug, Clone, Copy)]
 enum ComparisonType {
     #[strum(serialize = "<")]
     Le,
@@ -159,7 +176,7 @@ enum OutCome {
     Redirect(String),
 }
 
-#[derive(EnumString, PartialEq, Eq, Debug)]
+#[derive(EnumString, PartialEq, Eq, Debug, Clone, Copy)]
 enum Category {
     #[strum(serialize = "x")]
     X,
@@ -171,15 +188,18 @@ enum Category {
     S,
 }
 
-struct Rule {
+// A condition on one category of a part, and where the part goes if it's met.
+// Parsed rules send parts to a workflow by name, compiled ones by index
+#[derive(Debug)]
+struct Rule<T = OutCome> {
     category: Category,
     comparison_type: ComparisonType,
     amount: u32,
-    outcome: OutCome,
+    outcome: T,
 }
 
-impl Rule {
-    fn check(&self, part: &Part) -> Option<OutCome> {
+impl<T: Clone> Rule<T> {
+    fn check(&self, part: &Part) -> Option<T> {
         let category_value = part.category_for(&self.category);
 
         match self.comparison_type {
@@ -199,6 +219,82 @@ impl Rule {
             }
         }
     }
+
+    // Splits a range of parts into those that match the rule and those that
+    // don't, either of which might be empty
+    fn split(&self, range: PartRange) -> (Option<PartRange>, Option<PartRange>) {
+        let values = range.category_for(&self.category);
+
+        let (matching, not_matching) = match self.comparison_type {
+            // nothing is greater than u32::MAX, so a > rule there never matches
+            ComparisonType::Ge => (
+                values.start.max(self.amount.saturating_add(1))..values.end,
+                values.start..values.end.min(self.amount.saturating_add(1)),
+            ),
+            ComparisonType::Le => (
+                values.start..values.end.min(self.amount),
+                values.start.max(self.amount)..values.end,
+            ),
+        };
+
+        (
+            range.with_category(&self.category, matching),
+            range.with_category(&self.category, not_matching),
+        )
+    }
+}
+
+// A four dimensional box of parts, with a half open range of ratings for each category
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PartRange {
+    x: Range<u32>,
+    m: Range<u32>,
+    a: Range<u32>,
+    s: Range<u32>,
+}
+
+impl PartRange {
+    fn all() -> PartRange {
+        PartRange {
+            x: RATINGS,
+            m: RATINGS,
+            a: RATINGS,
+            s: RATINGS,
+        }
+    }
+
+    fn category_for(&self, cat: &Category) -> &Range<u32> {
+        match cat {
+            Category::X => &self.x,
+            Category::M => &self.m,
+            Category::A => &self.a,
+            Category::S => &self.s,
+        }
+    }
+
+    // A copy of this box with one category narrowed, or None if that leaves it empty
+    fn with_category(&self, cat: &Category, values: Range<u32>) -> Option<PartRange> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let mut range = self.clone();
+        match cat {
+            Category::X => range.x = values,
+            Category::M => range.m = values,
+            Category::A => range.a = values,
+            Category::S => range.s = values,
+        }
+
+        Some(range)
+    }
+
+    fn volume(&self) -> u64 {
+        [&self.x, &self.m, &self.a, &self.s]
+            .iter()
+            .map(|values| values.len() as u64)
+            .product()
+    }
 }
 
 struct Part {
@@ -269,13 +365,84 @@ mod test_super {
     fn test_part_one_example() {
         let part_one_answer = part_one_solution(&test_data());
 
-        assert_eq!(part_one_answer, 19114);
+        assert_eq!(part_one_answer, Ok(19114));
     }
 
     #[test]
     fn test_part_one_answer() {
         let data = process_input(include_str!("../data/puzzle_input.txt"));
 
-        assert_eq!(part_one_solution(&data), 374873);
+        assert_eq!(part_one_solution(&data), Ok(374873));
+    }
+
+    #[test]
+    fn test_rule_split() {
+        let rule = Rule {
+            category: Category::A,
+            comparison_type: ComparisonType::Le,
+            amount: 2006,
+            outcome: OutCome::Accepted,
+        };
+
+        let (matching, not_matching) = rule.split(PartRange::all());
+        assert_eq!(matching.unwrap().a, 1..2006);
+        assert_eq!(not_matching.unwrap().a, 2006..4001);
+
+        let rule = Rule {
+            category: Category::M,
+            comparison_type: ComparisonType::Ge,
+            amount: 2090,
+            outcome: OutCome::Accepted,
+        };
+
+        let (matching, not_matching) = rule.split(PartRange::all());
+        let (matching, not_matching) = (matching.unwrap(), not_matching.unwrap());
+        assert_eq!(matching.m, 2091..4001);
+        assert_eq!(not_matching.m, 1..2091);
+        assert_eq!(matching.x, RATINGS);
+
+        // nothing is left over once a range falls entirely on one side
+        let (matching, not_matching) = rule.split(PartRange {
+            m: 3000..3500,
+            ..PartRange::all()
+        });
+        assert!(matching.is_some());
+        assert_eq!(not_matching, None);
+
+        let rule = Rule {
+            category: Category::X,
+            comparison_type: ComparisonType::Ge,
+            amount: u32::MAX,
+            outcome: OutCome::Accepted,
+        };
+
+        let (matching, not_matching) = rule.split(PartRange::all());
+        assert_eq!(matching, None);
+        assert_eq!(not_matching, Some(PartRange::all()));
+    }
+
+    #[test]
+    fn test_part_range_volume() {
+        assert_eq!(PartRange::all().volume(), 256_000_000_000_000);
+
+        let range = PartRange {
+            x: 1..3,
+            m: 10..13,
+            a: 4000..4001,
+            s: 5..10,
+        };
+        assert_eq!(range.volume(), 30);
+    }
+
+    #[test]
+    fn test_part_two_example() {
+        assert_eq!(part_two_solution(&test_data()), Ok(167409079868000));
+    }
+
+    #[test]
+    fn test_part_two_answer() {
+        let data = process_input(include_str!("../data/puzzle_input.txt"));
+
+        assert_eq!(part_two_solution(&data), Ok(122112157518711));
     }
 }
@@ -0,0 +1,488 @@
+// Static checks over the parsed workflows, to catch anything that would make
+// classifying a part hang or fail before any parts are run through them.
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{index_workflows, OutCome, PartRange, Queue};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    // There's no workflow to start from
+    MissingStart {
+        name: String,
+    },
+    // A rule, or the default when `rule` is `None`, sends parts to a workflow
+    // that doesn't exist
+    UndefinedTarget {
+        workflow: String,
+        rule: Option<usize>,
+        target: String,
+    },
+    // A group of workflows that can send a part round in circles forever. It's
+    // only a problem if parts can get to it from the start
+    Cycle {
+        workflows: Vec<String>,
+        reachable: bool,
+    },
+    // No part can ever reach the workflow from the start
+    Unreachable {
+        workflow: String,
+    },
+    // The rule at this index can't match anything the earlier rules in the
+    // same workflow haven't already caught
+    DeadRule {
+        workflow: String,
+        rule: usize,
+    },
+    // The earlier rules catch every part, so the default is never used
+    DeadDefault {
+        workflow: String,
+    },
+}
+
+impl Diagnostic {
+    // Errors stop the workflows from classifying parts at all, anything else
+    // is just redundant
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Diagnostic::MissingStart { .. }
+                | Diagnostic::UndefinedTarget { .. }
+                | Diagnostic::Cycle {
+                    reachable: true,
+                    ..
+                }
+        )
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let level = if self.is_error() { "error" } else { "warning" };
+
+        match self {
+            Diagnostic::MissingStart { name } => {
+                write!(f, "{level}: there is no \"{name}\" workflow to start from")
+            }
+            Diagnostic::UndefinedTarget {
+                workflow,
+                rule: Some(rule),
+                target,
+            } => write!(
+                f,
+                "{level}: rule #{} of {workflow} sends parts to undefined workflow {target}",
+                rule + 1
+            ),
+            Diagnostic::UndefinedTarget {
+                workflow,
+                rule: None,
+                target,
+            } => write!(
+                f,
+                "{level}: {workflow} sends parts to undefined workflow {target} by default"
+            ),
+            Diagnostic::Cycle { workflows, .. } => {
+                write!(
+                    f,
+                    "{level}: workflows form a cycle: {}",
+                    workflows.join(", ")
+                )
+            }
+            Diagnostic::Unreachable { workflow } => {
+                write!(f, "{level}: {workflow} can never be reached")
+            }
+            Diagnostic::DeadRule { workflow, rule } => write!(
+                f,
+                "{level}: rule #{} of {workflow} can never match",
+                rule + 1
+            ),
+            Diagnostic::DeadDefault { workflow } => {
+                write!(f, "{level}: the default of {workflow} is never used")
+            }
+        }
+    }
+}
+
+pub fn validate(queues: &HashMap<String, Queue>, start: &str) -> Vec<Diagnostic> {
+    // sorted so the diagnostics always come out in the same order
+    let (names, indices) = index_workflows(queues);
+
+    let mut diagnostics = vec![];
+    // only the rules and defaults that can actually be used lead anywhere
+    let mut edges: Vec<Vec<usize>> = vec![vec![]; names.len()];
+
+    if !queues.contains_key(start) {
+        diagnostics.push(Diagnostic::MissingStart {
+            name: start.to_string(),
+        });
+    }
+
+    for (index, name) in names.iter().enumerate() {
+        let queue = &queues[*name];
+        let live = check_rules(queue, &mut diagnostics);
+
+        let outcomes = queue
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(rule, r)| (Some(rule), &r.outcome, live[rule]))
+            .chain([(None, &queue.default, live[queue.rules.len()])]);
+
+        // the compiler resolves every target, used or not, so an undefined one
+        // is an error even on a dead rule
+        for (rule, outcome, is_live) in outcomes {
+            let OutCome::Redirect(target) = outcome else {
+                continue;
+            };
+
+            match indices.get(target.as_str()) {
+                Some(&target) if is_live => edges[index].push(target),
+                Some(_) => {}
+                None => diagnostics.push(Diagnostic::UndefinedTarget {
+                    workflow: name.to_string(),
+                    rule,
+                    target: target.clone(),
+                }),
+            }
+        }
+    }
+
+    let reachable = match indices.get(start) {
+        Some(&start) => reachable_from(start, &edges),
+        None => HashSet::new(),
+    };
+
+    if queues.contains_key(start) {
+        diagnostics.extend(
+            names
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !reachable.contains(index))
+                .map(|(_, name)| Diagnostic::Unreachable {
+                    workflow: name.to_string(),
+                }),
+        );
+    }
+
+    for component in strongly_connected_components(&edges) {
+        let is_cycle = component.len() > 1 || edges[component[0]].contains(&component[0]);
+
+        if is_cycle {
+            let is_reachable = reachable.contains(&component[0]);
+            let mut workflows: Vec<String> = component
+                .into_iter()
+                .map(|index| names[index].to_string())
+                .collect();
+            workflows.sort();
+
+            diagnostics.push(Diagnostic::Cycle {
+                workflows,
+                reachable: is_reachable,
+            });
+        }
+    }
+
+    diagnostics
+}
+
+// Reports the rules that can never match and returns whether each rule, then
+// the default at the end, can ever be used
+fn check_rules(queue: &Queue, diagnostics: &mut Vec<Diagnostic>) -> Vec<bool> {
+    let mut remaining = Some(PartRange::all());
+    let mut live = vec![];
+
+    for (index, rule) in queue.rules.iter().enumerate() {
+        let matching = match remaining {
+            Some(range) => {
+                let (matching, not_matching) = rule.split(range);
+                remaining = not_matching;
+                matching
+            }
+            None => None,
+        };
+
+        if matching.is_none() {
+            diagnostics.push(Diagnostic::DeadRule {
+                workflow: queue.name.clone(),
+                rule: index,
+            });
+        }
+        live.push(matching.is_some());
+    }
+
+    if remaining.is_none() {
+        diagnostics.push(Diagnostic::DeadDefault {
+            workflow: queue.name.clone(),
+        });
+    }
+    live.push(remaining.is_some());
+
+    live
+}
+
+fn reachable_from(start: usize, edges: &[Vec<usize>]) -> HashSet<usize> {
+    let mut reachable = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(index) = queue.pop_front() {
+        for &next in &edges[index] {
+            if reachable.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    reachable
+}
+
+// Tarjan's algorithm. Any component with more than one workflow in it, or a
+// single workflow that points at itself, is a cycle
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct State<'a> {
+        edges: &'a [Vec<usize>],
+        next_order: usize,
+        order: Vec<Option<usize>>,
+        low_link: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        components: Vec<Vec<usize>>,
+    }
+
+    fn visit(state: &mut State, node: usize) {
+        state.order[node] = Some(state.next_order);
+        state.low_link[node] = state.next_order;
+        state.next_order += 1;
+        state.stack.push(node);
+        state.on_stack[node] = true;
+
+        for &next in &state.edges[node] {
+            match state.order[next] {
+                None => {
+                    visit(state, next);
+                    state.low_link[node] = state.low_link[node].min(state.low_link[next]);
+                }
+                Some(order) if state.on_stack[next] => {
+                    state.low_link[node] = state.low_link[node].min(order);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(state.low_link[node]) == state.order[node] {
+            let mut component = vec![];
+
+            while let Some(member) = state.stack.pop() {
+                state.on_stack[member] = false;
+                component.push(member);
+
+                if member == node {
+                    break;
+                }
+            }
+
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        edges,
+        next_order: 0,
+        order: vec![None; edges.len()],
+        low_link: vec![0; edges.len()],
+        stack: vec![],
+        on_stack: vec![false; edges.len()],
+        components: vec![],
+    };
+
+    for node in 0..edges.len() {
+        if state.order[node].is_none() {
+            visit(&mut state, node);
+        }
+    }
+
+    state.components
+}
+
+#[cfg(test)]
+mod test_super {
+    use super::*;
+    use crate::{decision_tree::DecisionTree, part_one_solution, process_input};
+
+    fn validate_workflows(workflows: &str) -> Vec<Diagnostic> {
+        let (queues, _) = process_input(&format!("{workflows}\n\n{{x=1,m=1,a=1,s=1}}"));
+
+        validate(&queues, "in")
+    }
+
+    #[test]
+    fn test_valid_workflows() {
+        assert_eq!(validate_workflows("in{x>10:a,R}\na{m<5:A,R}"), vec![]);
+
+        let (queues, _) = process_input(include_str!("../data/test_input.txt"));
+        assert_eq!(validate(&queues, "in"), vec![]);
+    }
+
+    #[test]
+    fn test_missing_start() {
+        assert_eq!(
+            validate_workflows("start{x>10:A,R}"),
+            vec![Diagnostic::MissingStart {
+                name: "in".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_undefined_targets() {
+        assert_eq!(
+            validate_workflows("in{x>10:a,m<5:A,b}"),
+            vec![
+                Diagnostic::UndefinedTarget {
+                    workflow: "in".to_string(),
+                    rule: Some(0),
+                    target: "a".to_string()
+                },
+                Diagnostic::UndefinedTarget {
+                    workflow: "in".to_string(),
+                    rule: None,
+                    target: "b".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unreachable() {
+        assert_eq!(
+            validate_workflows("in{x>10:A,R}\nb{m<5:A,c}\nc{s>5:A,R}"),
+            vec![
+                Diagnostic::Unreachable {
+                    workflow: "b".to_string()
+                },
+                Diagnostic::Unreachable {
+                    workflow: "c".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cycles() {
+        let diagnostics = validate_workflows("in{x>10:a,b}\na{m<5:b,A}\nb{s>5:a,R}\nc{c}");
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::Unreachable {
+                    workflow: "c".to_string()
+                },
+                Diagnostic::Cycle {
+                    workflows: vec!["a".to_string(), "b".to_string()],
+                    reachable: true
+                },
+                Diagnostic::Cycle {
+                    workflows: vec!["c".to_string()],
+                    reachable: false
+                },
+            ]
+        );
+        assert!(diagnostics[1].is_error());
+        // nothing can get to c, so it can't trap any parts
+        assert!(!diagnostics[2].is_error());
+    }
+
+    #[test]
+    fn test_unreachable_cycle_is_not_fatal() {
+        let workflows = "in{x<100:R,A}\nc{c}";
+        let diagnostics = validate_workflows(workflows);
+
+        assert!(!diagnostics.iter().any(Diagnostic::is_error));
+        assert_eq!(
+            diagnostics.last().unwrap().to_string(),
+            "warning: workflows form a cycle: c"
+        );
+
+        let data = process_input(&format!("{workflows}\n\n{{x=1,m=1,a=1,s=1}}"));
+        assert_eq!(part_one_solution(&data), Ok(0));
+    }
+
+    #[test]
+    fn test_agrees_with_compiler() {
+        // the undefined target is on a dead rule, which the compiler still resolves
+        for workflows in [
+            "in{x<100:R,x<50:zz,R}",
+            "in{x<100:R,x<50:A,zz}\nzz{A}",
+            "in{x>0:A,zz}",
+            "in{x<100:R,A}\nc{c}",
+        ] {
+            let data = process_input(&format!("{workflows}\n\n{{x=1,m=1,a=1,s=1}}"));
+            let is_valid = !validate(&data.0, "in").iter().any(Diagnostic::is_error);
+
+            assert_eq!(
+                is_valid,
+                DecisionTree::compile(&data.0, "in").is_ok(),
+                "{workflows}"
+            );
+        }
+
+        assert_eq!(
+            validate_workflows("in{x<100:R,x<50:zz,R}"),
+            vec![
+                Diagnostic::DeadRule {
+                    workflow: "in".to_string(),
+                    rule: 1
+                },
+                Diagnostic::UndefinedTarget {
+                    workflow: "in".to_string(),
+                    rule: Some(1),
+                    target: "zz".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dead_rules() {
+        let diagnostics = validate_workflows("in{x<100:R,x<50:A,m>0:A,s<5:R,a}\na{A}");
+
+        // m>0 matches every part left, so nothing gets past it. That also means
+        // a is only referenced from the dead default
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::DeadRule {
+                    workflow: "in".to_string(),
+                    rule: 1
+                },
+                Diagnostic::DeadRule {
+                    workflow: "in".to_string(),
+                    rule: 3
+                },
+                Diagnostic::DeadDefault {
+                    workflow: "in".to_string()
+                },
+                Diagnostic::Unreachable {
+                    workflow: "a".to_string()
+                },
+            ]
+        );
+        assert!(!diagnostics.iter().any(Diagnostic::is_error));
+    }
+
+    #[test]
+    fn test_display() {
+        let diagnostics = validate_workflows("in{x>10:a,x>20:A,b}\nb{b}");
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "warning: rule #2 of in can never match",
+                "error: rule #1 of in sends parts to undefined workflow a",
+                "error: workflows form a cycle: b",
+            ]
+        );
+    }
+}